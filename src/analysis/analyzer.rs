@@ -0,0 +1,70 @@
+use {
+    crate::*,
+    anyhow::Result,
+    serde::Deserialize,
+};
+
+/// The analyzer of the output of a job's command: it classifies
+/// the lines and builds the report.
+///
+/// Most analyzers only need to classify lines, as the default report
+/// building groups them into errors, test failures and warnings.
+pub trait Analyzer {
+    /// Analyze a line of output of the command
+    fn analyze_line(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> LineAnalysis;
+
+    /// Build the report from all the lines of output of the command
+    fn build_report(
+        &self,
+        cmd_lines: &[CommandOutputLine],
+    ) -> Result<Report> {
+        Report::from_lines(cmd_lines, self)
+    }
+}
+
+/// The choice of an analyzer, as given in the `analyzer` field of a job
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyzerRef {
+    /// The analyzer suited to the standard cargo commands:
+    /// check, clippy, test, etc.
+    #[default]
+    Standard,
+    /// The analyzer of cargo-nextest's output
+    Nextest,
+    /// The analyzer of the output of `cargo bench`, with either the
+    /// libtest harness or criterion
+    Bench,
+    /// The analyzer of the diagnostics of gcc, clang, and other
+    /// tools using the same "file:line:col: error: message" format
+    Gnu,
+}
+
+impl AnalyzerRef {
+    /// Create the analyzer, which will apply the given user defined
+    /// line rules before its own logic.
+    ///
+    /// `unstyled` tells whether the output is known to be written without
+    /// style, in which case diagnostics are recognized by their text
+    pub fn create_analyzer(
+        self,
+        line_rules: Vec<LineRule>,
+        unstyled: bool,
+    ) -> Box<dyn Analyzer> {
+        match self {
+            Self::Standard => Box::new(StandardAnalyzer {
+                line_rules,
+                unstyled,
+            }),
+            Self::Nextest => Box::new(NextestAnalyzer::new(line_rules, unstyled)),
+            Self::Bench => Box::new(BenchAnalyzer {
+                line_rules,
+                unstyled,
+            }),
+            Self::Gnu => Box::new(GnuAnalyzer { line_rules }),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use {
+    crate::*,
+    anyhow::Result,
+    lazy_regex::*,
+};
+
+/// The analyzer of the output of `cargo bench`, with either the
+/// libtest harness or [criterion](https://github.com/bheisler/criterion.rs)
+///
+/// Each benchmark is a report item, a regression detected by criterion
+/// being a warning.
+#[derive(Debug, Default, Clone)]
+pub struct BenchAnalyzer {
+    /// user defined rules, applied before the standard analysis
+    pub line_rules: Vec<LineRule>,
+    /// whether the output is known to be written without style
+    pub unstyled: bool,
+}
+
+impl Analyzer for BenchAnalyzer {
+    fn analyze_line(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> LineAnalysis {
+        analyze_with_rules(&self.line_rules, cmd_line).unwrap_or_else(|| {
+            if self.unstyled {
+                LineAnalysis::from_unstyled(cmd_line)
+            } else {
+                LineAnalysis::from(cmd_line)
+            }
+        })
+    }
+
+    fn build_report(
+        &self,
+        cmd_lines: &[CommandOutputLine],
+    ) -> Result<Report> {
+        let mut report = Report::from_lines(cmd_lines, self)?;
+        for (bench, details) in parse_benches(cmd_lines) {
+            let (kind, csi, title) = match bench.verdict {
+                BenchVerdict::Regressed => (Kind::Warning, CSI_BOLD_YELLOW, "regression"),
+                _ => (Kind::Bench, CSI_BOLD_BLUE, "bench"),
+            };
+            let mut lines = vec![Line {
+                item_idx: 0, // will be filled by push_item
+                line_type: LineType::Title(kind),
+                content: TLine::title_with(csi, title.to_string(), &bench.message()),
+            }];
+            lines.extend(details.into_iter().map(|content| Line {
+                item_idx: 0,
+                line_type: LineType::Normal,
+                content,
+            }));
+            if let Some(item) = report.push_item(lines) {
+                item.bench = Some(bench);
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Extract the benchmarks and the lines detailing them
+fn parse_benches(cmd_lines: &[CommandOutputLine]) -> Vec<(Bench, Vec<TLine>)> {
+    let mut benches: Vec<(Bench, Vec<TLine>)> = Vec::new();
+    // criterion writes long names on their own line, before the time
+    let mut pending_name = None;
+    // whether we're in the lines following a criterion benchmark
+    let mut in_details = false;
+    for cmd_line in cmd_lines {
+        let raw = cmd_line.content.to_raw();
+        if let Some(bench) = as_libtest_bench(&raw) {
+            benches.push((bench, Vec::new()));
+            pending_name = None;
+            in_details = false;
+            continue;
+        }
+        if let Some((_, name, time)) =
+            regex_captures!(r"^(\S.*?)?\s+time:\s+\[\S+ \S+ (\S+ \S+) \S+ \S+\]", &raw)
+        {
+            let name = if name.is_empty() {
+                pending_name.take()
+            } else {
+                Some(name.to_string())
+            };
+            if let (Some(name), Some(time)) = (name, parse_time(time)) {
+                let bench = Bench {
+                    name,
+                    time,
+                    change: None,
+                    verdict: BenchVerdict::Unknown,
+                    previous_time: None,
+                };
+                benches.push((bench, vec![cmd_line.content.clone()]));
+                in_details = true;
+                continue;
+            }
+        }
+        if in_details {
+            if cmd_line.content.is_blank() {
+                in_details = false;
+                continue;
+            }
+            if let Some((bench, details)) = benches.last_mut() {
+                if let Some((_, change)) =
+                    regex_captures!(r"change:\s+\[\S+%\s+([+-]?\d+(?:\.\d+)?)%\s+\S+%\]", &raw)
+                {
+                    bench.change = change.parse().ok();
+                }
+                if raw.contains("Performance has regressed") {
+                    bench.verdict = BenchVerdict::Regressed;
+                } else if raw.contains("Performance has improved") {
+                    bench.verdict = BenchVerdict::Improved;
+                } else if raw.contains("No change in performance detected")
+                    || raw.contains("Change within noise threshold")
+                {
+                    bench.verdict = BenchVerdict::NoChange;
+                }
+                details.push(cmd_line.content.clone());
+            }
+            continue;
+        }
+        pending_name = Some(raw.trim().to_string())
+            .filter(|name| !name.is_empty() && !raw.starts_with(char::is_whitespace))
+            .filter(|name| !name.starts_with("Benchmarking "));
+    }
+    benches
+}
+
+/// Return the benchmark when the line is a result of libtest's
+/// bench harness, for example
+///
+///  "test bench_parse ... bench:       1,234 ns/iter (+/- 56)"
+fn as_libtest_bench(s: &str) -> Option<Bench> {
+    let (_, name, time) = regex_captures!(
+        r"^test (\S+)\s+\.\.\. bench:\s+([\d,]+(?:\.\d+)?) ns/iter",
+        s
+    )?;
+    Some(Bench {
+        name: name.to_string(),
+        time: parse_time(&format!("{time} ns"))?,
+        change: None,
+        verdict: BenchVerdict::Unknown,
+        previous_time: None,
+    })
+}
+
+#[test]
+fn test_bench_report() {
+    let output = r#"
+     Running benches/fib.rs (target/release/deps/fib-5b3e5a6e1b2a7c1d)
+Gnuplot not found, using plotters backend
+fib 20                  time:   [26.029 µs 26.251 µs 26.505 µs]
+                        change: [+10.044% +12.311% +14.685%] (p = 0.00 < 0.05)
+                        Performance has regressed.
+Found 3 outliers among 100 measurements (3.00%)
+  3 (3.00%) high mild
+
+a/very/long/benchmark/name
+                        time:   [1.2001 ms 1.2104 ms 1.2215 ms]
+                        change: [-1.2% +0.31% +1.9%] (p = 0.71 > 0.05)
+                        No change in performance detected.
+
+     Running benches/old.rs (target/release/deps/old-0c9d3b1f0e2a4b6c)
+
+running 1 test
+test bench_parse ... bench:       1,234 ns/iter (+/- 56)
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 1 measured; 0 filtered out; finished in 0.45s
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdOut);
+    let report = BenchAnalyzer::default().build_report(&cmd_lines).unwrap();
+    assert_eq!(report.stats.warnings, 1);
+    assert_eq!(report.stats.benches, 2);
+    let benches: Vec<&Bench> = report
+        .items
+        .iter()
+        .filter_map(|item| item.bench.as_ref())
+        .collect();
+    assert_eq!(benches.len(), 3);
+    assert_eq!(benches[0].name, "fib 20");
+    assert!((benches[0].time - 26_251.0).abs() < 0.001);
+    assert_eq!(benches[0].change, Some(12.311));
+    assert_eq!(benches[0].verdict, BenchVerdict::Regressed);
+    assert_eq!(benches[1].name, "a/very/long/benchmark/name");
+    assert_eq!(benches[1].verdict, BenchVerdict::NoChange);
+    assert_eq!(benches[2].name, "bench_parse");
+    assert_eq!(benches[2].time, 1234.0);
+    assert_eq!(
+        report.lines[0].content.to_raw(),
+        "regression: fib 20: 26.251 µs (+12.31%)"
+    );
+}
@@ -0,0 +1,134 @@
+use {
+    crate::*,
+    lazy_regex::*,
+};
+
+/// The analyzer of the output of tools writing their diagnostics in
+/// the GNU format, like gcc, clang, protoc, or many linters:
+///
+///  "src/parser.c:12:5: error: 'count' undeclared"
+///
+/// The lines following a diagnostic (notes, excerpts of code) are
+/// part of its item.
+#[derive(Debug, Default, Clone)]
+pub struct GnuAnalyzer {
+    /// user defined rules, applied before the GNU analysis
+    pub line_rules: Vec<LineRule>,
+}
+
+impl Analyzer for GnuAnalyzer {
+    fn analyze_line(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> LineAnalysis {
+        if let Some(analysis) = analyze_with_rules(&self.line_rules, cmd_line) {
+            return analysis;
+        }
+        let raw = cmd_line.content.to_raw();
+        if let Some((location, kind, message)) = as_gnu_diagnostic(&raw) {
+            return LineAnalysis {
+                location: Some(location),
+                message: Some(message.to_string()),
+                ..LineAnalysis::of_type(LineType::Title(kind))
+            };
+        }
+        if regex_is_match!(
+            r"^\d+ (?:errors?|warnings?)(?: and \d+ (?:errors?|warnings?))? generated\.$",
+            &raw
+        ) || regex_is_match!(r"^compilation terminated\.$", &raw)
+            || regex_is_match!(r"^g?make(?:\[\d+\])?: \*\*\* ", &raw)
+        {
+            // sums, which end the current item
+            return LineAnalysis::of_type(LineType::Title(Kind::Sum));
+        }
+        LineAnalysis::normal()
+    }
+}
+
+/// Return the location, the kind and the message of a diagnostic when
+/// the line is in the GNU format, for example
+///
+///  "src/parser.c:12:5: error: 'count' undeclared (first use in this function)"
+/// or
+///  "api.proto:7:3: warning: Import google/protobuf/empty.proto is unused."
+/// or
+///  "lib/util.h:3: fatal error: config.h: No such file or directory"
+///
+/// Notes ("src/parser.c:8:1: note: declared here") aren't diagnostics
+/// but lines of the previous one.
+fn as_gnu_diagnostic(s: &str) -> Option<(String, Kind, &str)> {
+    let (_, path, line, column, kind, message) = regex_captures!(
+        r"^((?:[A-Za-z]:)?[^\s:][^:]*?):(\d+)(?::(\d+))?: (fatal error|error|warning): (.+)$",
+        s
+    )?;
+    let kind = match kind {
+        "warning" => Kind::Warning,
+        _ => Kind::Error,
+    };
+    let location = if column.is_empty() {
+        format!("{path}:{line}")
+    } else {
+        format!("{path}:{line}:{column}")
+    };
+    Some((location, kind, message))
+}
+
+#[test]
+fn test_gnu_report() {
+    let output = r#"
+cc -c -o parser.o src/parser.c
+src/parser.c: In function 'parse':
+src/parser.c:12:5: error: 'count' undeclared (first use in this function)
+   12 |     count++;
+      |     ^~~~~
+src/parser.c:12:5: note: each undeclared identifier is reported only once for each function it appears in
+src/parser.c:20:9: warning: unused variable 'tmp' [-Wunused-variable]
+   20 |     int tmp;
+      |         ^~~
+lib/util.h:3: fatal error: config.h: No such file or directory
+compilation terminated.
+make: *** [Makefile:4: parser.o] Error 1
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdErr);
+    let report = GnuAnalyzer::default().build_report(&cmd_lines).unwrap();
+    assert_eq!(report.stats.errors, 2);
+    assert_eq!(report.stats.warnings, 1);
+    let items: Vec<(Kind, String, Option<&str>)> = report
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.kind,
+                item.location.as_ref().unwrap().to_string(),
+                item.message.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (
+                Kind::Error,
+                "src/parser.c:12:5".to_string(),
+                Some("'count' undeclared (first use in this function)"),
+            ),
+            (
+                Kind::Error,
+                "lib/util.h:3".to_string(),
+                Some("config.h: No such file or directory"),
+            ),
+            (
+                Kind::Warning,
+                "src/parser.c:20:9".to_string(),
+                Some("unused variable 'tmp' [-Wunused-variable]"),
+            ),
+        ]
+    );
+    // the note and the excerpt of code belong to the error
+    let first_item_lines = report
+        .lines
+        .iter()
+        .filter(|line| line.item_idx == 1)
+        .count();
+    assert_eq!(first_item_lines, 5);
+}
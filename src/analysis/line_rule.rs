@@ -0,0 +1,192 @@
+use {
+    crate::*,
+    lazy_regex::*,
+    serde::Deserialize,
+    std::fmt,
+};
+
+/// A user defined rule classifying the lines matching a regular expression,
+/// applied before the analyzer's own logic.
+///
+/// The regular expression may have these named captures:
+/// - `kind`: "error" or "warning" (otherwise the kind of the rule is used)
+/// - `path`, `line`, `column`: the location of the problem
+/// - `message`: the message, used as title of the item
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "LineRuleConfig")]
+pub struct LineRule {
+    regex: Regex,
+    line_type: LineRuleType,
+    kind: Kind,
+}
+
+/// The type of line a rule produces
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LineRuleType {
+    /// the start of an error or warning (possibly with its location)
+    #[default]
+    Title,
+    /// a line locating the problem of the current item
+    Location,
+}
+
+/// The kind of item a title rule produces when the kind isn't captured
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LineRuleKind {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// A line rule, as written in the configuration
+#[derive(Debug, Clone, Deserialize)]
+struct LineRuleConfig {
+    regex: String,
+    #[serde(default)]
+    line_type: LineRuleType,
+    #[serde(default)]
+    kind: LineRuleKind,
+}
+
+#[derive(Debug)]
+pub struct LineRuleError {
+    regex: String,
+    error: regex::Error,
+}
+
+impl fmt::Display for LineRuleError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "Invalid line rule regex {:?}: {}",
+            self.regex, self.error
+        )
+    }
+}
+
+impl std::error::Error for LineRuleError {}
+
+impl TryFrom<LineRuleConfig> for LineRule {
+    type Error = LineRuleError;
+    fn try_from(config: LineRuleConfig) -> Result<Self, LineRuleError> {
+        let regex = Regex::new(&config.regex).map_err(|error| LineRuleError {
+            regex: config.regex.clone(),
+            error,
+        })?;
+        let kind = match config.kind {
+            LineRuleKind::Error => Kind::Error,
+            LineRuleKind::Warning => Kind::Warning,
+        };
+        Ok(Self {
+            regex,
+            line_type: config.line_type,
+            kind,
+        })
+    }
+}
+
+impl LineRule {
+    /// Return the analysis of the line if it matches the rule
+    pub fn analyze(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> Option<LineAnalysis> {
+        let raw = cmd_line.content.to_raw();
+        let caps = self.regex.captures(&raw)?;
+        let location = caps.name("path").map(|path| {
+            let mut location = path.as_str().to_string();
+            for part in ["line", "column"] {
+                if let Some(m) = caps.name(part) {
+                    location.push(':');
+                    location.push_str(m.as_str());
+                }
+            }
+            location
+        });
+        let line_type = match self.line_type {
+            LineRuleType::Title => {
+                let kind = match caps.name("kind") {
+                    Some(kind) => kind_from_str(kind.as_str())?,
+                    None => self.kind,
+                };
+                LineType::Title(kind)
+            }
+            LineRuleType::Location => {
+                location.as_ref()?;
+                LineType::Location
+            }
+        };
+        let message = caps.name("message").map(|m| m.as_str().to_string());
+        Some(LineAnalysis {
+            line_type,
+            key: None,
+            location,
+            message,
+        })
+    }
+}
+
+/// Return the analysis of the line by the first rule matching it, if any
+pub fn analyze_with_rules(
+    rules: &[LineRule],
+    cmd_line: &CommandOutputLine,
+) -> Option<LineAnalysis> {
+    rules.iter().find_map(|rule| rule.analyze(cmd_line))
+}
+
+/// Return the kind of item matching a captured kind, if it's a
+/// known one (other ones, like "note", don't start items)
+fn kind_from_str(s: &str) -> Option<Kind> {
+    match s.trim().to_lowercase().as_str() {
+        "error" | "err" | "fatal" | "fatal error" => Some(Kind::Error),
+        "warning" | "warn" => Some(Kind::Warning),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_line_rules() {
+    #[derive(Deserialize)]
+    struct Config {
+        line_rules: Vec<LineRule>,
+    }
+    let toml = r#"
+    [[line_rules]]
+    regex = '^(?P<path>[^:\s]+):(?P<line>\d+):(?P<column>\d+): (?P<kind>\w+): (?P<message>.+)$'
+    [[line_rules]]
+    regex = '^\s+in (?P<path>\S+) at line (?P<line>\d+)$'
+    line_type = "location"
+    [[line_rules]]
+    regex = '^DEPRECATED: (?P<message>.+)$'
+    kind = "warning"
+    "#;
+    let conf = toml::from_str::<Config>(toml).unwrap();
+    let analyze = |line: &str| {
+        analyze_with_rules(
+            &conf.line_rules,
+            &CommandOutputLine {
+                content: TLine::from_tty(line),
+                origin: CommandStream::StdErr,
+                ..Default::default()
+            },
+        )
+    };
+    let analysis = analyze("gen/schema.rs:12:5: error: unknown field `id`").unwrap();
+    assert_eq!(analysis.line_type, LineType::Title(Kind::Error));
+    assert_eq!(analysis.location.as_deref(), Some("gen/schema.rs:12:5"));
+    assert_eq!(analysis.message.as_deref(), Some("unknown field `id`"));
+    assert!(analyze("gen/schema.rs:12:5: note: defined here").is_none());
+    let analysis = analyze("   in gen/schema.rs at line 3").unwrap();
+    assert_eq!(analysis.line_type, LineType::Location);
+    assert_eq!(analysis.location.as_deref(), Some("gen/schema.rs:3"));
+    let analysis = analyze("DEPRECATED: the v1 schema").unwrap();
+    assert_eq!(analysis.line_type, LineType::Title(Kind::Warning));
+    assert_eq!(analysis.location, None);
+    assert!(analyze("some other line").is_none());
+    assert!(toml::from_str::<Config>("[[line_rules]]\nregex = '('").is_err());
+}
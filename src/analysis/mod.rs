@@ -0,0 +1,15 @@
+mod analyzer;
+mod bench_analyzer;
+mod gnu_analyzer;
+mod line_rule;
+mod nextest_analyzer;
+mod standard_analyzer;
+
+pub use {
+    analyzer::*,
+    bench_analyzer::*,
+    gnu_analyzer::*,
+    line_rule::*,
+    nextest_analyzer::*,
+    standard_analyzer::*,
+};
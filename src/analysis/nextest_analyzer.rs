@@ -0,0 +1,242 @@
+use {
+    crate::*,
+    anyhow::Result,
+    lazy_regex::*,
+    std::collections::HashSet,
+};
+
+/// The analyzer of the output of [cargo-nextest](https://nexte.st/)
+///
+/// Compilation errors and warnings are handled as with the standard
+/// analyzer, while test results and failure outputs are recognized
+/// with nextest's format.
+#[derive(Debug, Default, Clone)]
+pub struct NextestAnalyzer {
+    /// user defined rules, applied before the nextest analysis
+    pub line_rules: Vec<LineRule>,
+    /// whether the output is known to be written without style
+    pub unstyled: bool,
+    /// the keys of the tests which eventually passed, so that the
+    /// failures of their previous tries are ignored (they're flaky,
+    /// not failing)
+    passed_tests: HashSet<String>,
+}
+
+impl NextestAnalyzer {
+    pub fn new(
+        line_rules: Vec<LineRule>,
+        unstyled: bool,
+    ) -> Self {
+        Self {
+            line_rules,
+            unstyled,
+            passed_tests: HashSet::new(),
+        }
+    }
+}
+
+impl Analyzer for NextestAnalyzer {
+    fn analyze_line(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> LineAnalysis {
+        if let Some(analysis) = analyze_with_rules(&self.line_rules, cmd_line) {
+            return analysis;
+        }
+        let raw = cmd_line.content.to_raw();
+        if let Some((key, passed)) = as_test_result(&raw) {
+            if !passed && self.passed_tests.contains(key) {
+                return LineAnalysis::normal();
+            }
+            return LineAnalysis::with_key(LineType::TestResult(passed), key.to_string());
+        }
+        if let Some(key) = as_fail_output_title(&raw) {
+            if self.passed_tests.contains(key) {
+                return LineAnalysis::normal();
+            }
+            return LineAnalysis::with_key(LineType::Title(Kind::TestFail), key.to_string());
+        }
+        if regex_is_match!(r"^\s*Summary \[", &raw)
+            || regex_is_match!(r"^error: test run failed", &raw)
+        {
+            return LineAnalysis::of_type(LineType::Title(Kind::Sum));
+        }
+        let analysis = if self.unstyled {
+            LineAnalysis::from_unstyled(cmd_line)
+        } else {
+            LineAnalysis::from(cmd_line)
+        };
+        match analysis.line_type {
+            // the output of a test failure contains libtest's output,
+            // which must not be taken for results or sections
+            LineType::TestResult(_) | LineType::Title(Kind::TestFail) => LineAnalysis::normal(),
+            LineType::Title(Kind::Sum) if cmd_line.content.if_unstyled().is_some() => {
+                LineAnalysis::normal()
+            }
+            _ => analysis,
+        }
+    }
+
+    fn build_report(
+        &self,
+        cmd_lines: &[CommandOutputLine],
+    ) -> Result<Report> {
+        let passed_tests = cmd_lines
+            .iter()
+            .filter_map(|cmd_line| {
+                let raw = cmd_line.content.to_raw();
+                as_test_result(&raw)
+                    .filter(|(_, passed)| *passed)
+                    .map(|(key, _)| key.to_string())
+            })
+            .collect();
+        let analyzer = Self {
+            line_rules: self.line_rules.clone(),
+            unstyled: self.unstyled,
+            passed_tests,
+        };
+        let mut report = Report::from_lines(cmd_lines, &analyzer)?;
+        // the summary line is more reliable than our count of results
+        let summary = cmd_lines
+            .iter()
+            .rev()
+            .map(|cmd_line| cmd_line.content.to_raw())
+            .find(|raw| regex_is_match!(r"^\s*Summary \[", raw));
+        if let Some(summary) = summary {
+            fill_stats_from_summary(&mut report.stats, &summary);
+        }
+        Ok(report)
+    }
+}
+
+/// Return the key of the test and whether it passed when the line is
+/// the status line of a test, for example
+///
+///  "        PASS [   0.004s] bacon tests::some_test"
+/// or
+///  "     SIGSEGV [   0.035s] bacon tests::some_other_test"
+/// or
+///  "  TRY 2 FAIL [   0.005s] bacon tests::flaky_test"
+fn as_test_result(s: &str) -> Option<(&str, bool)> {
+    let (_, status, key) = regex_captures!(
+        r"^\s*(?:TRY \d+ )?([A-Z][A-Z\d-]*) \[\s*\d+(?:\.\d+)?s\]\s+(?:\(\s*\d+/\d+\)\s+)?(\S.*?)\s*$",
+        s
+    )?;
+    match status {
+        "PASS" | "LEAK" => Some((key, true)),
+        "FAIL" | "TIMEOUT" | "ABORT" | "LEAK-FAIL" => Some((key, false)),
+        signal if signal.starts_with("SIG") => Some((key, false)),
+        other => {
+            debug!("unrecognized nextest status: {:?}", other);
+            None
+        }
+    }
+}
+
+/// Return the key of the test when the line starts a section of
+/// the output of a failed test, for example
+///
+///  "--- STDOUT:              bacon tests::some_test ---"
+/// or
+///  "──── STDERR:             bacon tests::some_test"
+fn as_fail_output_title(s: &str) -> Option<&str> {
+    regex_captures!(
+        r"^\s*(?:---|────) (?:TRY \d+ )?(?:STDOUT|STDERR|OUTPUT):\s+(\S.*?)(?: ---)?\s*$",
+        s
+    )
+    .map(|(_, key)| key)
+}
+
+/// Fill the test counts of the stats from nextest's summary line, for example
+///
+///  "     Summary [   0.006s] 4 tests run: 2 passed (1 flaky), 2 failed, 1 skipped"
+fn fill_stats_from_summary(
+    stats: &mut Stats,
+    summary: &str,
+) {
+    let count = |name| {
+        summary
+            .split([',', '(', ')', ':'])
+            .filter_map(|part| regex_captures!(r"^\s*(\d+) ([a-z ]+?)\s*$", part))
+            .find(|(_, _, part_name)| *part_name == name)
+            .and_then(|(_, n, _)| n.parse().ok())
+    };
+    if let Some(passed) = count("passed") {
+        stats.passed_tests = passed;
+    }
+    if let Some(skipped) = count("skipped") {
+        stats.skipped_tests = skipped;
+    }
+    if let Some(flaky) = count("flaky") {
+        stats.flaky_tests = flaky;
+    }
+}
+
+#[test]
+fn test_nextest_report() {
+    let output = r#"
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.04s
+    Starting 4 tests across 1 binary (1 test skipped)
+        PASS [   0.003s] bacon tests::passing
+  TRY 1 FAIL [   0.003s] bacon tests::flaky
+--- TRY 1 STDOUT:        bacon tests::flaky ---
+
+running 1 test
+test tests::flaky ... FAILED
+
+   TRY 2 PASS [   0.003s] bacon tests::flaky
+     SIGSEGV [   0.012s] bacon tests::crashing
+        FAIL [   0.004s] bacon tests::failing
+--- STDOUT:              bacon tests::failing ---
+
+running 1 test
+test tests::failing ... FAILED
+
+failures:
+
+failures:
+    tests::failing
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 3 filtered out; finished in 0.00s
+
+--- STDERR:              bacon tests::failing ---
+thread 'tests::failing' panicked at src/lib.rs:12:9:
+assertion failed: false
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+------------
+     Summary [   0.015s] 4 tests run: 2 passed (1 flaky), 2 failed, 1 skipped
+     SIGSEGV [   0.012s] bacon tests::crashing
+        FAIL [   0.004s] bacon tests::failing
+error: test run failed
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdOut);
+    let report = NextestAnalyzer::default().build_report(&cmd_lines).unwrap();
+    let titles: Vec<String> = report
+        .lines
+        .iter()
+        .filter(|line| line.line_type == LineType::Title(Kind::TestFail))
+        .map(|line| line.content.to_raw())
+        .collect();
+    assert_eq!(
+        titles,
+        vec![
+            "failed: bacon tests::failing: assertion failed: false",
+            "failed: bacon tests::crashing",
+        ]
+    );
+    // the failing test's output contains both its stdout and its stderr
+    let failing_output: Vec<String> = report
+        .lines
+        .iter()
+        .filter(|line| line.item_idx == 1)
+        .map(|line| line.content.to_raw())
+        .collect();
+    assert!(failing_output.contains(&"test tests::failing ... FAILED".to_string()));
+    assert!(failing_output.contains(&"assertion failed: false".to_string()));
+    assert!(report.suggest_backtrace);
+    assert_eq!(report.stats.test_fails, 2);
+    assert_eq!(report.stats.passed_tests, 2);
+    assert_eq!(report.stats.flaky_tests, 1);
+    assert_eq!(report.stats.skipped_tests, 1);
+}
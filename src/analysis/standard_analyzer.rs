@@ -0,0 +1,130 @@
+use {
+    crate::*,
+    anyhow::Result,
+    lazy_regex::*,
+};
+
+/// The analyzer of cargo's standard output: compilation errors
+/// and warnings, test results, etc.
+#[derive(Debug, Default, Clone)]
+pub struct StandardAnalyzer {
+    /// user defined rules, applied before the standard analysis
+    pub line_rules: Vec<LineRule>,
+    /// whether the output is known to be written without style
+    pub unstyled: bool,
+}
+
+impl Analyzer for StandardAnalyzer {
+    fn analyze_line(
+        &self,
+        cmd_line: &CommandOutputLine,
+    ) -> LineAnalysis {
+        analyze_with_rules(&self.line_rules, cmd_line).unwrap_or_else(|| {
+            if self.unstyled {
+                LineAnalysis::from_unstyled(cmd_line)
+            } else {
+                LineAnalysis::from(cmd_line)
+            }
+        })
+    }
+
+    fn build_report(
+        &self,
+        cmd_lines: &[CommandOutputLine],
+    ) -> Result<Report> {
+        let mut report = Report::from_lines(cmd_lines, self)?;
+        for cmd_line in cmd_lines {
+            add_test_summary(&mut report.stats, &cmd_line.content.to_raw());
+        }
+        Ok(report)
+    }
+}
+
+/// Add the counts and duration of libtest's summary of a test binary
+/// to the stats, if the line is such a summary, for example
+///
+///  "test result: FAILED. 12 passed; 1 failed; 3 ignored; 0 measured; 5 filtered out; finished in 1.23s"
+fn add_test_summary(
+    stats: &mut Stats,
+    line: &str,
+) {
+    let Some((_, ignored, measured, filtered_out, duration)) = regex_captures!(
+        r"^test result: \w+\. \d+ passed; \d+ failed; (\d+) ignored; (\d+) measured; (\d+) filtered out(?:; finished in (\d+(?:\.\d+)?)s)?",
+        line
+    ) else {
+        return;
+    };
+    stats.ignored_tests += ignored.parse::<usize>().unwrap_or(0);
+    stats.measured_tests += measured.parse::<usize>().unwrap_or(0);
+    stats.filtered_out_tests += filtered_out.parse::<usize>().unwrap_or(0);
+    let duration = duration.parse::<f64>().unwrap_or(0.0);
+    *stats.test_duration.get_or_insert(0.0) += duration;
+}
+
+#[test]
+fn test_test_summaries() {
+    let output = r#"
+running 3 tests
+test tests::a ... ok
+test tests::b ... ignored
+test tests::c ... ok
+
+test result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 4 filtered out; finished in 1.25s
+
+running 2 tests
+test src/lib.rs - f (line 3) ... ok
+test src/lib.rs - g (line 12) ... ignored
+
+test result: ok. 1 passed; 0 failed; 1 ignored; 1 measured; 0 filtered out; finished in 0.50s
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdOut);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    assert_eq!(report.stats.passed_tests, 3);
+    assert_eq!(report.stats.ignored_tests, 2);
+    assert_eq!(report.stats.measured_tests, 1);
+    assert_eq!(report.stats.filtered_out_tests, 4);
+    assert_eq!(report.stats.test_duration, Some(1.75));
+}
+
+#[test]
+fn test_program_output_in_test_failure() {
+    let output = r#"
+running 1 test
+test tests::connect ... FAILED
+
+failures:
+
+---- tests::connect stdout ----
+error: connection refused
+thread 'tests::connect' panicked at src/lib.rs:12:9:
+no server
+
+failures:
+    tests::connect
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdOut);
+    let program_line = &cmd_lines[7];
+    assert_eq!(program_line.content.to_raw(), "error: connection refused");
+    // unless the output is known to be unstyled, diagnostics are
+    // recognized by their style, not by their text
+    let analyzer = StandardAnalyzer::default();
+    assert_eq!(
+        analyzer.analyze_line(program_line).line_type,
+        LineType::Normal
+    );
+    let report = analyzer.build_report(&cmd_lines).unwrap();
+    assert_eq!(report.stats.test_fails, 1);
+    assert_eq!(report.stats.all_errors(), 0);
+    let unstyled_analyzer = StandardAnalyzer {
+        unstyled: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        unstyled_analyzer.analyze_line(program_line).line_type,
+        LineType::Title(Kind::Error)
+    );
+}
@@ -3,18 +3,13 @@ use {
     anyhow::Result,
     crokey::*,
     crossbeam::channel::{
-        bounded,
-        select,
-    },
-    notify::event::{
-        AccessKind,
-        AccessMode,
-        DataChange,
-        EventKind,
-        ModifyKind,
+        Receiver,
+        RecvError,
+        Select,
     },
     termimad::{
         EventSource,
+        TimedEvent,
         crossterm::event::Event,
     },
 };
@@ -28,106 +23,91 @@ use {
     },
 };
 
-/// Run the mission and return the reference to the next job to run, if any
+/// What woke the application up
+enum AppEvent {
+    /// a change in the files watched by the tab with this index
+    Watch(usize),
+    /// some information about the task of the tab with this index
+    Exec(usize, Result<CommandExecInfo, RecvError>),
+    /// a key, a resize, etc.
+    User(Result<TimedEvent, RecvError>),
+}
+
+/// Run the missions, each one in its own tab, and return the
+/// reference to the next job to run, if any
 pub fn run(
     w: &mut W,
-    mission: Mission,
+    missions: Vec<Mission>,
     event_source: &EventSource,
 ) -> Result<Option<JobRef>> {
-    let keybindings = mission.settings.keybindings.clone();
-    let mut ignorer = time!(Info, mission.ignorer());
-    let (watch_sender, watch_receiver) = bounded(0);
-    let on_change_strategy = mission
-        .job
-        .on_change_strategy
-        .or(mission.settings.on_change_strategy)
-        .unwrap_or(OnChangeStrategy::WaitThenRestart);
-    let mut watcher =
-        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
-            Ok(we) => {
-                match we.kind {
-                    EventKind::Modify(ModifyKind::Metadata(_)) => {
-                        info!("ignoring metadata change");
-                        return; // useless event
-                    }
-                    EventKind::Modify(ModifyKind::Data(DataChange::Any)) => {
-                        info!("ignoring 'any' data change");
-                        return; // probably useless event with no real change
-                    }
-                    EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
-                        info!("close write event: {we:?}");
-                    }
-                    EventKind::Access(_) => {
-                        info!("ignoring access event: {we:?}");
-                        return; // probably useless event
-                    }
-                    _ => {
-                        info!("notify event: {we:?}");
-                    }
-                }
-                if let Some(ignorer) = ignorer.as_mut() {
-                    match time!(Info, ignorer.excludes_all(&we.paths)) {
-                        Ok(true) => {
-                            debug!("all excluded");
-                            return;
-                        }
-                        Ok(false) => {
-                            debug!("at least one is included");
-                        }
-                        Err(e) => {
-                            warn!("exclusion check failed: {e}");
-                        }
-                    }
-                }
-                if let Err(e) = watch_sender.send(()) {
-                    debug!("error when notifying on inotify event: {}", e);
-                }
-            }
-            Err(e) => warn!("watch error: {:?}", e),
-        })?;
-
-    mission.add_watchs(&mut watcher)?;
-
-    let mut executor = MissionExecutor::new(&mission)?;
-
-    let mut state = AppState::new(mission)?;
-    state.computation_starts();
-    state.draw(w)?;
-
-    let mut task_executor = executor.start(state.new_task())?; // first computation
+    let Some(first_mission) = missions.first() else {
+        return Ok(None);
+    };
+    let keybindings = first_mission.settings.keybindings.clone();
+    let mut tabs = missions
+        .into_iter()
+        .map(Tab::new)
+        .collect::<Result<Vec<_>>>()?;
+    // the index of the displayed tab
+    let mut current = 0;
+    draw(w, &mut tabs, current)?;
 
     let user_events = event_source.receiver();
     let mut next_job: Option<JobRef> = None;
     #[allow(unused_mut)]
     loop {
-        let mut action: Option<&Action> = None;
-        select! {
-            recv(watch_receiver) -> _ => {
-                state.receive_watch_event();
-                if state.auto_refresh.is_enabled() {
-                    if !state.is_computing() || on_change_strategy == OnChangeStrategy::KillThenRestart {
-                        action = Some(&Action::Internal(Internal::ReRun));
-                    }
+        let mut action: Option<Action> = None;
+        // the index of the tab the action applies to
+        let mut action_tab = current;
+        match next_event(&tabs, &user_events) {
+            AppEvent::Watch(tab_idx) => {
+                let tab = &mut tabs[tab_idx];
+                tab.state.receive_watch_event();
+                if tab.state.auto_refresh.is_enabled()
+                    && (!tab.state.is_computing()
+                        || tab.on_change_strategy == OnChangeStrategy::KillThenRestart)
+                {
+                    action = Some(Action::Internal(Internal::ReRun));
+                    action_tab = tab_idx;
                 }
             }
-            recv(executor.line_receiver) -> info => {
+            AppEvent::Exec(tab_idx, info) => {
+                let state = &mut tabs[tab_idx].state;
                 if let Ok(info) = info {
                     match info {
                         CommandExecInfo::Line(line) => {
                             state.add_line(line);
                         }
-                        CommandExecInfo::End { status } => {
-                            info!("execution finished with status: {:?}", status);
+                        CommandExecInfo::End { status, usage } => {
+                            info!(
+                                "execution finished with status: {:?}, usage: {:?}",
+                                status, usage
+                            );
                             // computation finished
                             let output = state.take_output().unwrap_or_default();
-                            let cmd_result = CommandResult::new(output, status)?;
+                            let cmd_result = CommandResult::new(
+                                output,
+                                status,
+                                usage,
+                                &state.mission.stage_labels(),
+                                state.analyzer(),
+                            )?;
                             state.set_result(cmd_result);
-                            action = state.action();
+                            action = state.action().cloned();
+                            action_tab = tab_idx;
                         }
                         CommandExecInfo::Error(e) => {
                             warn!("error in computation: {}", e);
-                            state.computation_stops();
-                            break;
+                            // the other tabs go on
+                            state.computation_fails(&e);
+                        }
+                        CommandExecInfo::TimedOut { duration } => {
+                            info!("execution timed out after {:?}", duration);
+                            let output = state.take_output().unwrap_or_default();
+                            state
+                                .set_result(CommandResult::TimedOut(TimedOut { duration, output }));
+                            action = state.action().cloned();
+                            action_tab = tab_idx;
                         }
                         CommandExecInfo::Interruption => {
                             debug!("command was interrupted (by us)");
@@ -135,23 +115,51 @@ pub fn run(
                     }
                 }
             }
-            recv(user_events) -> user_event => {
+            AppEvent::User(user_event) => {
                 match user_event?.event {
                     Event::Resize(mut width, mut height) => {
-                        state.resize(width, height);
+                        for tab in &mut tabs {
+                            tab.state.resize(width, height);
+                        }
                     }
                     Event::Key(key_event) => {
                         let key_combination = KeyCombination::from(key_event);
                         debug!("key combination pressed: {}", key_combination);
-                        action = keybindings.get(key_combination);
+                        let tab = &mut tabs[current];
+                        match tab.state.apply_input_key(key_event) {
+                            InputAction::Send(input) => {
+                                if let Some(task_executor) = tab.task_executor() {
+                                    if let Err(e) = task_executor.send_input(&input) {
+                                        warn!("failed to send input to the job: {e}");
+                                    }
+                                }
+                            }
+                            InputAction::Close => {
+                                if let Some(task_executor) = tab.task_executor() {
+                                    if let Err(e) = task_executor.close_input() {
+                                        warn!("failed to close the input of the job: {e}");
+                                    }
+                                }
+                            }
+                            InputAction::Unhandled => {
+                                action = keybindings.get(key_combination).cloned();
+                            }
+                            InputAction::Edit | InputAction::Leave => {}
+                        }
                     }
                     #[cfg(windows)]
-                    Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollDown, .. }) => {
-                        action = keybindings.get(key!(down));
+                    Event::Mouse(MouseEvent {
+                        kind: MouseEventKind::ScrollDown,
+                        ..
+                    }) => {
+                        action = keybindings.get(key!(down)).cloned();
                     }
                     #[cfg(windows)]
-                    Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollUp, .. }) => {
-                        action = keybindings.get(key!(up));
+                    Event::Mouse(MouseEvent {
+                        kind: MouseEventKind::ScrollUp,
+                        ..
+                    }) => {
+                        action = keybindings.get(key!(up)).cloned();
                     }
                     _ => {}
                 }
@@ -159,15 +167,23 @@ pub fn run(
             }
         }
         info!("action: {action:?}");
+        if action_tab != current && action.as_ref().is_some_and(|a| !is_tab_action(a)) {
+            // the end of a job in a hidden tab mustn't change the
+            // displayed tab nor stop the other ones
+            info!("action ignored in a background tab");
+            action = None;
+        }
         if let Some(action) = action.take() {
             debug!("requested action: {action:?}");
+            let tab = &mut tabs[action_tab];
+            let state = &mut tab.state;
             match action {
                 Action::Export(export_name) => {
                     state
                         .mission
                         .settings
                         .exports
-                        .do_named_export(export_name, &state);
+                        .do_named_export(&export_name, state);
                 }
                 Action::Internal(internal) => match internal {
                     Internal::Back => {
@@ -176,20 +192,27 @@ pub fn run(
                             break;
                         }
                     }
+                    Internal::CycleGrouping => {
+                        state.cycle_grouping();
+                    }
                     Internal::Help => {
                         state.toggle_help();
                     }
+                    Internal::NextTab => {
+                        current = (current + 1) % tabs.len();
+                    }
+                    Internal::PreviousTab => {
+                        current = (current + tabs.len() - 1) % tabs.len();
+                    }
                     Internal::Quit => {
                         break;
                     }
                     Internal::Refresh => {
                         state.clear();
-                        task_executor.die();
-                        task_executor = state.start_computation(&mut executor)?;
+                        tab.rerun();
                     }
                     Internal::ReRun => {
-                        task_executor.die();
-                        task_executor = state.start_computation(&mut executor)?;
+                        tab.rerun();
                     }
                     Internal::ToggleRawOutput => {
                         state.toggle_raw_output();
@@ -200,13 +223,20 @@ pub fn run(
                     Internal::ToggleWrap => {
                         state.toggle_wrap_mode();
                     }
+                    Internal::ToggleGroup => {
+                        state.toggle_group();
+                    }
+                    Internal::ToggleInput => {
+                        state.toggle_input();
+                    }
+                    Internal::ToggleAllGroups => {
+                        state.toggle_all_groups();
+                    }
                     Internal::ToggleBacktrace => {
                         state.toggle_backtrace();
-                        task_executor.die();
-                        task_executor = state.start_computation(&mut executor)?;
+                        tab.rerun();
                     }
                     Internal::Scroll(scroll_command) => {
-                        let scroll_command = *scroll_command;
                         state.apply_scroll_command(scroll_command);
                     }
                     Internal::Pause => {
@@ -215,10 +245,9 @@ pub fn run(
                     Internal::Unpause => {
                         if state.changes_since_last_job_start > 0 {
                             state.clear();
-                            task_executor.die();
-                            task_executor = state.start_computation(&mut executor)?;
+                            tab.rerun();
                         }
-                        state.auto_refresh = AutoRefresh::Enabled;
+                        tab.state.auto_refresh = AutoRefresh::Enabled;
                     }
                     Internal::TogglePause => match state.auto_refresh {
                         AutoRefresh::Enabled => {
@@ -227,21 +256,90 @@ pub fn run(
                         AutoRefresh::Paused => {
                             if state.changes_since_last_job_start > 0 {
                                 state.clear();
-                                task_executor.die();
-                                task_executor = state.start_computation(&mut executor)?;
+                                tab.rerun();
                             }
-                            state.auto_refresh = AutoRefresh::Enabled;
+                            tab.state.auto_refresh = AutoRefresh::Enabled;
                         }
                     },
                 },
                 Action::Job(job_ref) => {
-                    next_job = Some((*job_ref).clone());
-                    break;
+                    // a job which already has its tab is just shown
+                    let tab_idx = match &job_ref {
+                        JobRef::Concrete(concrete) => {
+                            let job_name = concrete.to_string();
+                            tabs.iter()
+                                .position(|tab| tab.state.mission.job_name == job_name)
+                        }
+                        _ => None,
+                    };
+                    if let Some(tab_idx) = tab_idx.filter(|_| tabs.len() > 1) {
+                        current = tab_idx;
+                    } else {
+                        next_job = Some(job_ref);
+                        break;
+                    }
                 }
             }
         }
-        state.draw(w)?;
+        draw(w, &mut tabs, current)?;
+    }
+    // all tasks are killed before any is waited for, so that
+    // their grace periods run together
+    for tab in &tabs {
+        tab.kill();
+    }
+    for tab in &mut tabs {
+        tab.die();
     }
-    task_executor.die();
     Ok(next_job)
 }
+
+/// Tell whether the action only changes the tab it's applied to, and
+/// not the set of tabs or the displayed one
+fn is_tab_action(action: &Action) -> bool {
+    !matches!(
+        action,
+        Action::Job(_)
+            | Action::Internal(
+                Internal::Back | Internal::NextTab | Internal::PreviousTab | Internal::Quit
+            )
+    )
+}
+
+/// Wait for the next event, coming from any tab or from the user
+fn next_event(
+    tabs: &[Tab],
+    user_events: &Receiver<TimedEvent>,
+) -> AppEvent {
+    let mut select = Select::new();
+    for tab in tabs {
+        select.recv(&tab.watch_receiver);
+        select.recv(&tab.executor.line_receiver);
+    }
+    let user_idx = select.recv(user_events);
+    let oper = select.select();
+    let idx = oper.index();
+    if idx == user_idx {
+        return AppEvent::User(oper.recv(user_events));
+    }
+    let tab_idx = idx / 2;
+    let tab = &tabs[tab_idx];
+    if idx % 2 == 0 {
+        let _ = oper.recv(&tab.watch_receiver);
+        AppEvent::Watch(tab_idx)
+    } else {
+        AppEvent::Exec(tab_idx, oper.recv(&tab.executor.line_receiver))
+    }
+}
+
+/// Draw the current tab, with the tab bar when there are several tabs
+fn draw(
+    w: &mut W,
+    tabs: &mut [Tab],
+    current: usize,
+) -> Result<()> {
+    let bar = (tabs.len() > 1).then(|| tab_bar(tabs, current));
+    let state = &mut tabs[current].state;
+    state.tab_bar = bar;
+    state.draw(w)
+}
@@ -92,6 +92,10 @@ pub struct Args {
     #[clap(short = 'j', long, value_name = "job")]
     pub job: Option<ConcreteJobRef>,
 
+    /// Comma separated list of jobs to run concurrently, in tabs
+    #[clap(long, value_name = "jobs", value_delimiter = ',')]
+    pub tabs: Vec<ConcreteJobRef>,
+
     /// Ignore features of both the package and the bacon job
     #[clap(long)]
     pub no_default_features: bool,
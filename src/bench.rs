@@ -0,0 +1,84 @@
+use {
+    lazy_regex::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The result of a benchmark, as given by libtest or criterion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bench {
+    pub name: String,
+    /// the estimated time of an iteration, in nanoseconds
+    pub time: f64,
+    /// the change of time, in percents, measured by criterion
+    /// against its own baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<f64>,
+    #[serde(default)]
+    pub verdict: BenchVerdict,
+    /// the time of the same benchmark in the previous run of the
+    /// job in bacon, in nanoseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_time: Option<f64>,
+}
+
+/// The conclusion of criterion about the change of performance
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchVerdict {
+    /// no comparison was made (first run, or libtest benchmark)
+    #[default]
+    Unknown,
+    /// no significant change, or a change within the noise threshold
+    NoChange,
+    Improved,
+    Regressed,
+}
+
+impl Bench {
+    /// Return the change of time since the previous run, in percents
+    pub fn change_since_previous(&self) -> Option<f64> {
+        self.previous_time
+            .filter(|&previous| previous > 0.0)
+            .map(|previous| (self.time - previous) * 100.0 / previous)
+    }
+    /// Return the message of the report item of the benchmark,
+    /// for example "fib 20: 26.251 µs (+0.30%)"
+    pub fn message(&self) -> String {
+        let mut message = format!("{}: {}", self.name, format_time(self.time));
+        if let Some(change) = self.change {
+            message.push_str(&format!(" ({change:+.2}%)"));
+        }
+        message
+    }
+}
+
+/// Parse a time with its unit, as written by criterion (eg "26.251 µs")
+/// or libtest (eg "1,234.50 ns"), and return it in nanoseconds
+pub fn parse_time(s: &str) -> Option<f64> {
+    let (_, value, unit) = regex_captures!(r"^([\d,]+(?:\.\d+)?)\s*(ps|ns|µs|us|ms|s)$", s.trim())?;
+    let value: f64 = value.replace(',', "").parse().ok()?;
+    let factor = match unit {
+        "ps" => 0.001,
+        "ns" => 1.0,
+        "µs" | "us" => 1_000.0,
+        "ms" => 1_000_000.0,
+        _ => 1_000_000_000.0,
+    };
+    Some(value * factor)
+}
+
+/// Format a time given in nanoseconds with the most readable unit
+pub fn format_time(ns: f64) -> String {
+    if ns < 1_000.0 {
+        format!("{ns:.3} ns")
+    } else if ns < 1_000_000.0 {
+        format!("{:.3} µs", ns / 1_000.0)
+    } else if ns < 1_000_000_000.0 {
+        format!("{:.3} ms", ns / 1_000_000.0)
+    } else {
+        format!("{:.3} s", ns / 1_000_000_000.0)
+    }
+}
@@ -0,0 +1,161 @@
+use {
+    crate::*,
+    cargo_metadata::{
+        Message,
+        diagnostic::{
+            Diagnostic,
+            DiagnosticLevel,
+            DiagnosticSpan,
+        },
+    },
+    lazy_regex::*,
+};
+
+/// Return the cargo message when the line is a record of the output
+/// of cargo called with `--message-format=json` (or one of its variants,
+/// like `json-diagnostic-rendered-ansi`).
+pub fn as_cargo_message(cmd_line: &CommandOutputLine) -> Option<Message> {
+    let content = cmd_line.content.if_unstyled()?.trim_end();
+    if !content.starts_with(r#"{"reason":"#) {
+        return None;
+    }
+    match serde_json::from_str(content) {
+        Ok(message) => Some(message),
+        Err(e) => {
+            // probably a reason we don't know
+            debug!("unparsable cargo message: {e}");
+            None
+        }
+    }
+}
+
+/// Build the report lines of a diagnostic emitted by the compiler
+/// and received as a cargo JSON message.
+///
+/// The lines are the ones of the rendered diagnostic, so that they
+/// look the same as without JSON, but their types come from the
+/// level and spans of the diagnostic, not from their style.
+///
+/// Return None when the diagnostic isn't a report item (for example
+/// a sum like "aborting due to 2 previous errors")
+pub fn diagnostic_lines(diagnostic: &Diagnostic) -> Option<(Kind, Vec<Line>)> {
+    let (kind, csi) = match diagnostic.level {
+        DiagnosticLevel::Error
+            if regex_is_match!(r"^linking with `.+` failed", &diagnostic.message) =>
+        {
+            (Kind::Link, CSI_BOLD_RED)
+        }
+        DiagnosticLevel::Error | DiagnosticLevel::Ice => (Kind::Error, CSI_BOLD_RED),
+        DiagnosticLevel::Warning => (Kind::Warning, CSI_BOLD_YELLOW),
+        _ => {
+            return None; // notes, helps, failure-notes
+        }
+    };
+    if diagnostic.spans.is_empty() && is_sum_message(&diagnostic.message) {
+        return None;
+    }
+    let mut title = match diagnostic.level {
+        DiagnosticLevel::Ice => "error: internal compiler error".to_string(),
+        DiagnosticLevel::Error => "error".to_string(),
+        _ => "warning".to_string(),
+    };
+    let mut lint = None;
+    if let Some(code) = &diagnostic.code {
+        // lint names are also given as codes but rustc doesn't display them in titles
+        if regex_is_match!(r"^E\d+$", &code.code) {
+            title = format!("{title}[{}]", code.code);
+        } else {
+            lint = Some(code.code.as_str());
+        }
+    }
+    let mut content = TLine::title_with(csi, title, &diagnostic.message);
+    if let Some(lint) = lint {
+        // the rendered note naming the lint is only given on its first occurrence
+        content.add_code(lint);
+    }
+    let mut lines = vec![Line {
+        item_idx: 0, // will be filled later
+        line_type: LineType::Title(kind),
+        content,
+    }];
+    let primary_location = diagnostic
+        .spans
+        .iter()
+        .find(|span| span.is_primary)
+        .map(span_location);
+    let mut location_found = false;
+    let rendered = diagnostic.rendered.as_deref().unwrap_or("");
+    // the first lines of the rendered diagnostic are the title, which we rebuilt
+    let title_height = diagnostic.message.lines().count().max(1);
+    for rendered_line in rendered.lines().skip(title_height) {
+        let content = TLine::from_tty(rendered_line);
+        let mut line_type = detail_line_type(&content.to_raw());
+        if !location_found {
+            if let Some(location) = &primary_location {
+                let raw = content.to_raw();
+                if raw.trim_start().strip_prefix("--> ") == Some(location.as_str()) {
+                    line_type = LineType::Location;
+                    location_found = true;
+                }
+            }
+        }
+        lines.push(Line {
+            item_idx: 0,
+            line_type,
+            content,
+        });
+    }
+    if !location_found {
+        if let Some(location) = primary_location {
+            // the rendered text didn't contain the location, we add our own
+            lines.insert(
+                1,
+                Line {
+                    item_idx: 0,
+                    line_type: LineType::Location,
+                    content: TLine::location(location),
+                },
+            );
+        }
+    }
+    Some((kind, lines))
+}
+
+/// Return the location of the span, the way rustc writes it, for example
+/// "src/main.rs:3:21"
+fn span_location(span: &DiagnosticSpan) -> String {
+    format!(
+        "{}:{}:{}",
+        span.file_name, span.line_start, span.column_start
+    )
+}
+
+/// Tell whether the message of a diagnostic without span is just
+/// a sum of the other ones
+fn is_sum_message(message: &str) -> bool {
+    regex_is_match!(r"^aborting due to", message)
+        || regex_is_match!(r"^\d+ warnings? emitted", message)
+}
+
+#[test]
+fn test_diagnostic_lines() {
+    let json = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/jt#0.1.0","manifest_path":"/tmp/jt/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"jt","src_path":"/tmp/jt/src/main.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: unused variable: `x`\n --> src/main.rs:2:9\n  |\n2 |     let x = 3;\n  |         ^ help: if this is intentional, prefix it with an underscore: `_x`\n  |\n  = note: `#[warn(unused_variables)]` on by default\n\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(unused_variables)]` on by default","rendered":null,"spans":[]}],"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":21,"byte_start":20,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":10,"highlight_start":9,"text":"    let x = 3;"}]}],"code":{"code":"unused_variables","explanation":null}}}"#;
+    let cmd_line = CommandOutputLine {
+        content: TLine::from_tty(json),
+        origin: CommandStream::StdOut,
+        ..Default::default()
+    };
+    let Some(Message::CompilerMessage(message)) = as_cargo_message(&cmd_line) else {
+        panic!("compiler message not recognized");
+    };
+    let (kind, lines) = diagnostic_lines(&message.message).unwrap();
+    assert_eq!(kind, Kind::Warning);
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0].line_type, LineType::Title(Kind::Warning));
+    assert_eq!(lines[0].title_message(), Some("unused variable: `x`"));
+    assert_eq!(lines[0].content.code(), Some("unused_variables"));
+    assert_eq!(lines[1].line_type, LineType::Location);
+    assert_eq!(lines[1].location(), Some("src/main.rs:2:9"));
+    assert_eq!(lines[6].line_type, LineType::Note);
+    assert!(lines[2..].iter().all(|l| l.line_type.is_detail()));
+}
@@ -79,12 +79,10 @@ pub fn run() -> anyhow::Result<()> {
     let workspace_config_path = location.workspace_config_path();
     let package_config_path = location.package_config_path();
 
-    if package_config_path != workspace_config_path {
-        if workspace_config_path.exists() {
-            info!("loading workspace level bacon.toml");
-            let workspace_config = Config::from_path(&workspace_config_path)?;
-            settings.apply_config(&workspace_config);
-        }
+    if package_config_path != workspace_config_path && workspace_config_path.exists() {
+        info!("loading workspace level bacon.toml");
+        let workspace_config = Config::from_path(&workspace_config_path)?;
+        settings.apply_config(&workspace_config);
     }
 
     if args.init {
@@ -129,18 +127,21 @@ pub fn run() -> anyhow::Result<()> {
     let mut result = Ok(());
     #[allow(clippy::while_let_loop)]
     loop {
-        let (job_name, job) = match job_stack.pick_job(&next_job) {
+        let jobs = match job_stack.pick_jobs(&next_job) {
             Err(e) => {
                 result = Err(e);
                 break;
             }
-            Ok(Some(t)) => t,
+            Ok(Some(jobs)) => jobs,
             Ok(None) => {
                 break;
             }
         };
-        let r = Mission::new(&location, job_name.to_string(), job, &settings)
-            .and_then(|mission| app::run(&mut w, mission, &event_source));
+        let r = jobs
+            .into_iter()
+            .map(|(job_name, job)| Mission::new(&location, job_name.to_string(), job, &settings))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .and_then(|missions| app::run(&mut w, missions, &event_source));
         match r {
             Ok(Some(job_ref)) => {
                 next_job = job_ref;
@@ -4,20 +4,37 @@ use {
         Deserialize,
         Serialize,
     },
-    std::process::ExitStatus,
+    std::{
+        process::ExitStatus,
+        time::Duration,
+    },
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, Eq)]
 pub enum CommandStream {
     StdOut,
+    /// the stream which is always captured
+    #[default]
     StdErr,
 }
 
 /// a line coming either from stdout or from stderr
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CommandOutputLine {
     pub content: TLine,
     pub origin: CommandStream,
+    /// the number of the line in the output of the task, in the order
+    /// of reading (stdout and stderr being read concurrently, it's only
+    /// the order of writing when they're merged)
+    #[serde(default)]
+    pub seq: usize,
+    /// when the line was read, since the start of the task
+    #[serde(default)]
+    pub time: Duration,
+    /// the index of the stage of the job which wrote the line (always
+    /// 0 when the job has no steps)
+    #[serde(default)]
+    pub stage: usize,
 }
 
 /// some output lines
@@ -30,11 +47,18 @@ pub struct CommandOutput {
 #[derive(Debug)]
 pub enum CommandExecInfo {
     /// Command ended
-    End { status: Option<ExitStatus> },
+    End {
+        status: Option<ExitStatus>,
+        usage: Option<TaskUsage>,
+    },
 
     /// Bacon killed the command
     Interruption,
 
+    /// Bacon killed the command because it ran for longer than the
+    /// timeout of the job
+    TimedOut { duration: Duration },
+
     /// Execution failed
     Error(String),
 
@@ -51,17 +75,78 @@ impl WrappableLine for CommandOutputLine {
     }
 }
 
+#[cfg(test)]
+impl CommandOutputLine {
+    /// Build the lines of an output made of the given contents, all
+    /// read, in order, on the same stream
+    pub fn from_contents<I: IntoIterator<Item = TLine>>(
+        contents: I,
+        origin: CommandStream,
+    ) -> Vec<Self> {
+        contents
+            .into_iter()
+            .enumerate()
+            .map(|(seq, content)| Self {
+                content,
+                origin,
+                seq,
+                ..Default::default()
+            })
+            .collect()
+    }
+    /// Build the lines of an output from its text, styled with
+    /// escape sequences
+    pub fn from_tty_text(
+        text: &str,
+        origin: CommandStream,
+    ) -> Vec<Self> {
+        Self::from_contents(text.lines().map(TLine::from_tty), origin)
+    }
+}
+
 impl CommandOutput {
     pub fn reverse(&mut self) {
         self.lines.reverse()
     }
+    /// Add a line, after the ones with a lower sequence number (the lines
+    /// read on stdout and stderr may be received in a different order),
+    /// and return its index
     pub fn push(
         &mut self,
         line: CommandOutputLine,
-    ) {
-        self.lines.push(line);
+    ) -> usize {
+        let idx = self
+            .lines
+            .iter()
+            .rposition(|previous| previous.seq <= line.seq)
+            .map_or(0, |idx| idx + 1);
+        self.lines.insert(idx, line);
+        idx
     }
     pub fn len(&self) -> usize {
         self.lines.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+#[test]
+fn test_output_lines_order() {
+    let mut output = CommandOutput::default();
+    for (seq, origin) in [
+        (0, CommandStream::StdOut),
+        (2, CommandStream::StdOut),
+        (1, CommandStream::StdErr),
+        (3, CommandStream::StdErr),
+    ] {
+        output.push(CommandOutputLine {
+            content: TLine::from_raw(seq.to_string()),
+            origin,
+            seq,
+            ..Default::default()
+        });
+    }
+    let lines: Vec<String> = output.lines.iter().map(|l| l.content.to_raw()).collect();
+    assert_eq!(lines, vec!["0", "1", "2", "3"]);
 }
@@ -15,6 +15,8 @@ pub enum CommandResult {
     Report(Report),
     /// we don't have a proper report
     Failure(Failure),
+    /// the command was killed on timeout, we only have a partial output
+    TimedOut(TimedOut),
     /// not yet computed
     None,
 }
@@ -23,19 +25,27 @@ impl CommandResult {
     pub fn new(
         output: CommandOutput,
         exit_status: Option<ExitStatus>,
+        usage: Option<TaskUsage>,
+        stage_labels: &[String],
+        analyzer: &dyn Analyzer,
     ) -> Result<Self> {
         let lines = &output.lines;
         let error_code = exit_status.and_then(|s| s.code()).filter(|&c| c != 0);
-        let mut report = Report::from_lines(lines)?;
+        let mut report = Report::of_stages(lines, stage_labels, analyzer)?;
         debug!("report stats: {:?}", &report.stats);
         if let Some(error_code) = error_code {
-            if report.stats.errors + report.stats.test_fails == 0 {
+            if report.stats.all_errors() + report.stats.test_fails == 0 {
                 // report shows no error while the command exe reported
                 // an error, so the report can't be trusted
-                return Ok(Self::Failure(Failure { error_code, output }));
+                return Ok(Self::Failure(Failure {
+                    error_code: Some(error_code),
+                    output,
+                    usage,
+                }));
             }
         }
         report.output = output;
+        report.usage = usage;
         // report looks valid
         Ok(Self::Report(report))
     }
@@ -44,10 +54,20 @@ impl CommandResult {
         match self {
             Self::Report(report) => Some(&report.output),
             Self::Failure(failure) => Some(&failure.output),
+            Self::TimedOut(timed_out) => Some(&timed_out.output),
             Self::None => None,
         }
     }
 
+    /// the resources used by the command, if it ended by itself
+    pub fn usage(&self) -> Option<&TaskUsage> {
+        match self {
+            Self::Report(report) => report.usage.as_ref(),
+            Self::Failure(failure) => failure.usage.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn report(&self) -> Option<&Report> {
         match self {
             Self::Report(report) => Some(report),
@@ -60,7 +80,7 @@ impl CommandResult {
     pub fn is_success(&self) -> bool {
         match self {
             Self::Report(report) => {
-                report.stats.errors + report.stats.warnings + report.stats.test_fails == 0
+                report.stats.all_errors() + report.stats.warnings + report.stats.test_fails == 0
             }
             _ => false,
         }
@@ -74,6 +94,9 @@ impl CommandResult {
             Self::Failure(failure) => {
                 failure.output.reverse();
             }
+            Self::TimedOut(timed_out) => {
+                timed_out.output.reverse();
+            }
             Self::None => {}
         }
     }
@@ -81,6 +104,7 @@ impl CommandResult {
         match self {
             Self::Report(report) => report.lines.len(),
             Self::Failure(failure) => failure.output.lines.len(),
+            Self::TimedOut(timed_out) => timed_out.output.lines.len(),
             Self::None => 0,
         }
     }
@@ -33,11 +33,17 @@ pub struct Config {
 
     pub help_line: Option<bool>,
 
+    pub grouping: Option<Grouping>,
+
     #[serde(default)]
     pub jobs: HashMap<String, Job>,
 
     pub keybindings: Option<KeyBindings>,
 
+    /// line rules applying to all jobs
+    #[serde(default)]
+    pub line_rules: Vec<LineRule>,
+
     pub on_change_strategy: Option<OnChangeStrategy>,
 
     pub reverse: Option<bool>,
@@ -46,6 +52,11 @@ pub struct Config {
 
     pub summary: Option<bool>,
 
+    pub summary_helps: Option<bool>,
+
+    /// jobs run concurrently, each one in its own tab
+    pub tabs: Option<Vec<ConcreteJobRef>>,
+
     #[deprecated(since = "2.0.0", note = "use keybindings")]
     pub vim_keys: Option<bool>,
 
@@ -60,7 +71,13 @@ impl Config {
             if !regex_is_match!(r#"^[\w-]+$"#, name) {
                 bail!("Invalid configuration : Illegal job name : {:?}", name);
             }
-            if job.command.is_empty() {
+            if !job.command.is_empty() && !job.steps.is_empty() {
+                bail!(
+                    "Invalid configuration : both command and steps for job {:?}",
+                    name
+                );
+            }
+            if job.stages().iter().any(|command| command.is_empty()) {
                 bail!("Invalid configuration : empty command for job {:?}", name);
             }
         }
@@ -0,0 +1,81 @@
+use {
+    lazy_regex::*,
+    serde::Deserialize,
+    std::{
+        fmt,
+        time::Duration,
+    },
+};
+
+/// A duration, as written in the configuration, with its unit,
+/// for example "500ms", "2s", "1.5m" or "1h"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ConfigDuration(pub Duration);
+
+#[derive(Debug)]
+pub struct ConfigDurationError {
+    s: String,
+}
+
+impl fmt::Display for ConfigDurationError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "Invalid duration {:?} (expected a number and a unit, eg \"500ms\" or \"2s\")",
+            self.s
+        )
+    }
+}
+
+impl std::error::Error for ConfigDurationError {}
+
+impl TryFrom<String> for ConfigDuration {
+    type Error = ConfigDurationError;
+    fn try_from(s: String) -> Result<Self, ConfigDurationError> {
+        let err = || ConfigDurationError { s: s.clone() };
+        let (_, value, unit) =
+            regex_captures!(r"^(\d+(?:\.\d+)?)\s*(ms|s|m|h)$", s.trim()).ok_or_else(err)?;
+        let value: f64 = value.parse().map_err(|_| err())?;
+        let secs = match unit {
+            "ms" => value / 1000.0,
+            "s" => value,
+            "m" => value * 60.0,
+            _ => value * 3600.0,
+        };
+        Ok(Self(Duration::from_secs_f64(secs)))
+    }
+}
+
+/// Format a duration for display, eg "850ms", "12.3s" or "2m05s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if secs >= 1 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[test]
+fn test_config_duration() {
+    let parse = |s: &str| ConfigDuration::try_from(s.to_string()).ok().map(|d| d.0);
+    assert_eq!(parse("500ms"), Some(Duration::from_millis(500)));
+    assert_eq!(parse("2s"), Some(Duration::from_secs(2)));
+    assert_eq!(parse("1.5m"), Some(Duration::from_secs(90)));
+    assert_eq!(parse("1h"), Some(Duration::from_secs(3600)));
+    assert_eq!(parse("2"), None);
+    assert_eq!(parse("two seconds"), None);
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+    assert_eq!(format_duration(Duration::from_millis(12_340)), "12.3s");
+    assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+}
@@ -0,0 +1,127 @@
+use {
+    crate::*,
+    lazy_regex::*,
+    std::fs,
+};
+
+/// Return the location of a doctest from the key of its test, for example
+/// "src/lib.rs:121" for "src/lib.rs - mode::Mode::new (line 121)"
+///
+/// The line is the one of the code block's opening fence.
+pub fn doctest_location(key: &str) -> Option<Location> {
+    let (_, path, line) = regex_captures!(r"^(\S+) - (?:.* )?\(line (\d+)\)$", key)?;
+    Some(Location {
+        path: path.to_string(),
+        line: line.parse().ok(),
+        column: None,
+        label: None,
+    })
+}
+
+/// Return the number of lines of the code block of a doctest, whose opening
+/// fence is at the given line of the file, or None if the file can't be read
+/// (its path is relative to the directory where cargo runs, usually the
+/// current one)
+pub fn doctest_block_len(doctest: &Location) -> Option<usize> {
+    let fence_line = doctest.line?;
+    let content = fs::read_to_string(&doctest.path).ok()?;
+    let block_len = content
+        .lines()
+        .skip(fence_line)
+        .take_while(|line| !is_fence(line))
+        .count();
+    Some(block_len)
+}
+
+/// Tell whether the line, maybe in a doc comment, is the fence of a code block
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    let line = ["///", "//!", "/**", "*"]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .unwrap_or(line);
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Fix a location given in the output of a doctest when it's not a line
+/// of the code block in the file: rustdoc compiles the block apart, so the
+/// lines it reports are relative to the block, and the line in the file is
+/// the one of the opening fence plus this offset.
+///
+/// A location in the file of the doctest whose line is after the end of
+/// the block (when its length is known) is a real one, for example the
+/// one of a panic in a function called by the doctest, and isn't changed.
+///
+/// Return whether the location was changed
+pub fn fix_doctest_location(
+    doctest: &Location,
+    block_len: Option<usize>,
+    location: &mut Location,
+) -> bool {
+    let (Some(block_line), Some(line)) = (doctest.line, location.line) else {
+        return false;
+    };
+    if location.path != doctest.path || block_len.is_some_and(|block_len| line > block_len) {
+        return false;
+    }
+    location.line = Some(block_line + line);
+    true
+}
+
+#[test]
+fn test_doctest_locations() {
+    let doctest = doctest_location("src/mode.rs - mode::Mode::new (line 121)").unwrap();
+    assert_eq!(doctest.to_string(), "src/mode.rs:121");
+    assert_eq!(
+        doctest_location("src/lib.rs - (line 6)")
+            .unwrap()
+            .to_string(),
+        "src/lib.rs:6"
+    );
+    assert!(doctest_location("tests::some_test").is_none());
+    let mut location = Location::parse("src/mode.rs:1:5");
+    assert!(fix_doctest_location(&doctest, Some(6), &mut location));
+    assert_eq!(location.to_string(), "src/mode.rs:122:5");
+    let mut location = Location::parse("src/mode.rs:4:9");
+    assert!(fix_doctest_location(&doctest, Some(6), &mut location));
+    assert_eq!(location.to_string(), "src/mode.rs:125:9");
+    let mut location = Location::parse("src/mode.rs:125:5");
+    assert!(!fix_doctest_location(&doctest, Some(6), &mut location));
+    let mut location = Location::parse("src/lib.rs:2:5");
+    assert!(!fix_doctest_location(&doctest, Some(6), &mut location));
+}
+
+#[test]
+fn test_doctest_near_top_of_file() {
+    let path = std::env::temp_dir().join(format!("bacon-doctest-{}.rs", std::process::id()));
+    fs::write(
+        &path,
+        r#"//! The crate
+//!
+//! ```
+//! use my_crate::*;
+//!
+//! let mut mode = Mode::new();
+//! mode.toggle();
+//! assert!(mode.is_on());
+//! mode.toggle();
+//! assert!(mode.is_on());
+//! ```
+
+pub fn f() {}
+"#,
+    )
+    .unwrap();
+    let doctest = doctest_location(&format!("{} - (line 3)", path.display())).unwrap();
+    let block_len = doctest_block_len(&doctest);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(block_len, Some(7));
+    // the failing assertion, at the 7th line of the block
+    let mut location = Location::parse(&format!("{}:7:1", path.display()));
+    assert!(fix_doctest_location(&doctest, block_len, &mut location));
+    assert_eq!(location.line, Some(10));
+    // a location after the block isn't relative to it
+    let mut location = Location::parse(&format!("{}:13:1", path.display()));
+    assert!(!fix_doctest_location(&doctest, block_len, &mut location));
+}
@@ -6,21 +6,40 @@ use {
     },
     crossbeam::channel::{
         Receiver,
+        RecvTimeoutError,
         Sender,
     },
     std::{
+        fs::File,
         io::{
             self,
             BufRead,
             BufReader,
+            Write,
         },
         process::{
             Child,
+            ChildStdin,
             Command,
+            ExitStatus,
             Stdio,
         },
+        sync::{
+            Arc,
+            Mutex,
+            atomic::{
+                AtomicBool,
+                AtomicUsize,
+                Ordering,
+            },
+        },
         thread,
+        time::{
+            Duration,
+            Instant,
+        },
     },
+    termimad::crossterm::terminal,
 };
 
 /// an executor calling a cargo (or similar) command in a separate
@@ -28,10 +47,13 @@ use {
 /// and finishing by None.
 /// Channel sizes are designed to avoid useless computations.
 pub struct MissionExecutor {
-    command: Command,
-    kill_command: Option<Vec<String>>,
-    /// whether it's necessary to transmit stdout lines
-    with_stdout: bool,
+    launcher: StageLauncher,
+    kill: Kill,
+    /// how long the processes are given to end after the kill
+    /// signal, before they're sent SIGKILL
+    grace_period: Duration,
+    /// how long the command may run before being killed
+    timeout: Option<Duration>,
     line_sender: Sender<CommandExecInfo>,
     pub line_receiver: Receiver<CommandExecInfo>,
 }
@@ -42,6 +64,20 @@ pub struct TaskExecutor {
     /// the thread running the current task
     child_thread: thread::JoinHandle<()>,
     stop_sender: Sender<StopMessage>,
+    /// set when the task is killed, so that the lines its processes
+    /// may still write aren't sent anymore
+    killed: Arc<AtomicBool>,
+    /// where to write the input of the command of the current
+    /// stage, until it's closed
+    stdin: Arc<Mutex<Option<JobStdin>>>,
+}
+
+/// The input of the command of a task
+enum JobStdin {
+    /// the stdin pipe of the command
+    Pipe(ChildStdin),
+    /// the master side of the pseudo-terminal
+    Pty(File),
 }
 
 /// A message sent to the child_thread on end
@@ -57,16 +93,80 @@ pub struct Task {
     pub backtrace: bool,
 }
 
+/// Launches the commands of the stages of the job, in the task thread
+/// for all but the first one
+#[derive(Clone)]
+struct StageLauncher {
+    /// the commands of the stages, run in order (there's only one
+    /// when the job has no steps)
+    commands: Arc<Mutex<Vec<Command>>>,
+    /// whether it's necessary to transmit stdout lines
+    with_stdout: bool,
+    /// whether the commands are run on a pseudo-terminal
+    pty: bool,
+    /// whether stdout and stderr are written in the same pipe
+    merge_streams: bool,
+}
+
+/// The launched command of a stage
+struct RunningStage {
+    child: Child,
+    stdin: Option<JobStdin>,
+    /// where stdout and stderr are both read, when they're merged
+    merged_output: Option<File>,
+}
+
 impl TaskExecutor {
+    /// Write some input to the process
+    pub fn send_input(
+        &mut self,
+        input: &str,
+    ) -> io::Result<()> {
+        let mut job_stdin = self.stdin.lock().unwrap();
+        let stdin: &mut dyn Write = match job_stdin.as_mut() {
+            Some(JobStdin::Pipe(stdin)) => stdin,
+            Some(JobStdin::Pty(master)) => master,
+            None => {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "input closed"));
+            }
+        };
+        stdin.write_all(input.as_bytes())?;
+        stdin.flush()
+    }
+    /// Make the process read an end of file on its input
+    pub fn close_input(&mut self) -> io::Result<()> {
+        let mut job_stdin = self.stdin.lock().unwrap();
+        match job_stdin.as_mut() {
+            Some(JobStdin::Pipe(_)) => {
+                *job_stdin = None; // dropping the pipe closes it
+                Ok(())
+            }
+            // on a terminal, the end of file is a character (ctrl-d), and
+            // the input can still be used after
+            Some(JobStdin::Pty(master)) => master.write_all(&[4]),
+            None => Ok(()),
+        }
+    }
     /// Interrupt the process
     pub fn interrupt(self) {
-        let _ = self.stop_sender.send(StopMessage::Kill);
+        self.kill();
     }
-    /// Kill the process, and wait until it finished
-    pub fn die(self) {
+    /// Ask for the process to be killed, without waiting for it to
+    /// end: the task thread gives it the grace period, then reaps it
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::Relaxed);
         if let Err(e) = self.stop_sender.send(StopMessage::Kill) {
             debug!("failed to send 'die' signal: {e}");
         }
+    }
+    /// Kill the process, and wait until it finished
+    pub fn die(self) {
+        self.kill();
+        self.wait();
+    }
+    /// Wait until the task thread finished, after the end of all
+    /// the processes of the task
+    fn wait(self) {
         if self.child_thread.join().is_err() {
             warn!("child_thread.join() failed"); // should not happen
         }
@@ -76,159 +176,633 @@ impl TaskExecutor {
 impl MissionExecutor {
     /// Prepare the executor (no task/process/thread is started at this point)
     pub fn new(mission: &Mission) -> Result<Self> {
-        let mut command = mission.get_command();
-        let kill_command = mission.kill_command();
+        let mut commands = mission.get_commands();
+        let kill = mission.kill();
+        let grace_period = mission.grace_period();
+        let timeout = mission.timeout();
+        let with_stdin = mission.need_stdin();
         let with_stdout = mission.need_stdout();
+        let pty = mission.use_pty() && cfg!(target_os = "linux");
+        if mission.use_pty() && !pty {
+            warn!("pty jobs are only supported on Linux");
+        }
+        let merge_streams = mission.merge_streams() && !pty && cfg!(unix);
+        if mission.merge_streams() && !cfg!(unix) {
+            warn!("merging streams is only supported on unix");
+        }
         let (line_sender, line_receiver) = crossbeam::channel::unbounded();
-        command
-            .stdin(Stdio::null())
-            .stderr(Stdio::piped())
-            .stdout(if with_stdout {
-                Stdio::piped()
+        for command in &mut commands {
+            command
+                .stdin(if with_stdin {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                })
+                .stderr(Stdio::piped())
+                .stdout(if with_stdout {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                });
+            if pty {
+                // the session of the command is also its process group
+                set_pty_session(command);
+                // progress bars, drawn because the command sees a terminal,
+                // would only be noise in bacon
+                command.env("CARGO_TERM_PROGRESS_WHEN", "never");
             } else {
-                Stdio::null()
-            });
-        Ok(Self {
-            command,
-            kill_command,
+                // so that all the processes started by the command can be
+                // stopped together
+                #[cfg(unix)]
+                std::os::unix::process::CommandExt::process_group(command, 0);
+            }
+        }
+        let launcher = StageLauncher {
+            commands: Arc::new(Mutex::new(commands)),
             with_stdout,
+            pty,
+            merge_streams,
+        };
+        Ok(Self {
+            launcher,
+            kill,
+            grace_period,
+            timeout,
             line_sender,
             line_receiver,
         })
     }
 
-    /// Start the job's command, once, with the given settings
+    /// Start the job's command, once, with the given settings.
+    ///
+    /// When the job is a pipeline, its stages are run in order, until
+    /// one of them fails.
+    ///
+    /// The command is launched only after the end of the processes of
+    /// the previous task, if any, which should have been killed (so that
+    /// they release their resources, eg the port of a server), but the
+    /// caller isn't blocked: errors are sent as `CommandExecInfo::Error`.
     pub fn start(
         &mut self,
         task: Task,
+        previous: Option<TaskExecutor>,
     ) -> Result<TaskExecutor> {
         info!("start task {task:?}");
-        let mut child = self
-            .command
-            .env("RUST_BACKTRACE", if task.backtrace { "1" } else { "0" })
-            .spawn()
-            .context("failed to launch command")?;
-        let kill_command = self.kill_command.clone();
-        let with_stdout = self.with_stdout;
+        let launcher = self.launcher.clone();
+        let stdin = Arc::new(Mutex::new(None));
+        let task_stdin = Arc::clone(&stdin);
+        let killed = Arc::new(AtomicBool::new(false));
+        let task_killed = Arc::clone(&killed);
+        let job_kill = self.kill.clone();
+        let grace_period = self.grace_period;
+        let timeout = self.timeout;
         let line_sender = self.line_sender.clone();
         let (stop_sender, stop_receiver) = crossbeam::channel::bounded(1);
         let err_stop_sender = stop_sender.clone();
 
-        // Global task executor thread
+        // Global task executor thread, which ends after the last process of
+        // the task was reaped
         let child_thread = thread::spawn(move || {
-            // thread piping the stdout lines
-            if with_stdout {
-                let sender = line_sender.clone();
-                let Some(stdout) = child.stdout.take() else {
-                    warn!("process has no stdout"); // unlikely
+            if let Some(previous) = previous {
+                debug!("waiting for the end of the previous task");
+                previous.wait();
+            }
+            if task_killed.load(Ordering::Relaxed) {
+                debug!("task killed before its start");
+                return;
+            }
+            let start = Instant::now();
+            let stamper = LineStamper::new(start, task_killed);
+            let deadline = timeout.map(|timeout| start + timeout);
+            let mut running = match launcher.spawn(0, task) {
+                Ok(running) => running,
+                Err(e) => {
+                    let _ = line_sender.send(CommandExecInfo::Error(format!("{e:#}")));
                     return;
+                }
+            };
+            let mut stage = 0;
+            // the resources used by the previous stages
+            let mut previous_usage: Option<TaskUsage> = None;
+            loop {
+                *task_stdin.lock().unwrap() = running.stdin.take();
+                let mut child = running.child;
+                let stamper = stamper.for_stage(stage);
+                if let Some(reader) = running.merged_output {
+                    pipe_merged_output(reader, stamper, &line_sender, err_stop_sender.clone());
+                } else {
+                    pipe_output(
+                        &mut child,
+                        launcher.with_stdout,
+                        stamper,
+                        &line_sender,
+                        err_stop_sender.clone(),
+                    );
+                }
+
+                // whether the child was waited for, out of the std API
+                let mut reaped = false;
+
+                // now waiting for the stop event, or the timeout
+                let stop = match deadline {
+                    Some(deadline) => stop_receiver.recv_deadline(deadline),
+                    None => stop_receiver
+                        .recv()
+                        .map_err(|_| RecvTimeoutError::Disconnected),
                 };
-                let mut buf_reader = BufReader::new(stdout);
-                thread::spawn(move || {
-                    let mut line = String::new();
-                    loop {
-                        match buf_reader.read_line(&mut line) {
-                            Err(e) => {
-                                warn!("error : {e}");
-                            }
-                            Ok(0) => {
-                                // there won't be anything more, quitting
-                                break;
-                            }
-                            Ok(_) => {
-                                let response = CommandExecInfo::Line(CommandOutputLine {
-                                    content: TLine::from_tty(&line),
-                                    origin: CommandStream::StdOut,
-                                });
-                                if sender.send(response).is_err() {
-                                    break; // channel closed
+                match stop {
+                    Ok(stop) => match stop {
+                        StopMessage::SendStatus => match wait_ended(&mut child, start) {
+                            Ok(Some((status, usage))) => {
+                                reaped = true;
+                                let usage = match previous_usage {
+                                    Some(previous_usage) => previous_usage.then(usage),
+                                    None => usage,
+                                };
+                                if status.success() && stage + 1 < launcher.stage_count() {
+                                    stage += 1;
+                                    debug!("starting stage {stage}");
+                                    match launcher.spawn(stage, task) {
+                                        Ok(next) => {
+                                            running = next;
+                                            previous_usage = Some(usage);
+                                            continue;
+                                        }
+                                        Err(e) => {
+                                            let _ = line_sender
+                                                .send(CommandExecInfo::Error(format!("{e:#}")));
+                                            return;
+                                        }
+                                    }
                                 }
+                                let _ = line_sender.send(CommandExecInfo::End {
+                                    status: Some(status),
+                                    usage: Some(usage),
+                                });
                             }
-                        }
-                        line.clear();
-                    }
-                });
-            }
-
-            // starting a thread to handle stderr lines until program
-            // ends (then ask the child_thread to send status)
-            let err_line_sender = line_sender.clone();
-            let stderr = child.stderr.take().expect("child missing stderr");
-            let mut buf_reader = BufReader::new(stderr);
-            thread::spawn(move || {
-                let mut line = String::new();
-                loop {
-                    match buf_reader.read_line(&mut line) {
-                        Err(e) => {
-                            warn!("error : {e}");
-                        }
-                        Ok(0) => {
-                            if let Err(e) = err_stop_sender.send(StopMessage::SendStatus) {
-                                warn!("sending stop message failed: {e}");
+                            Ok(None) => {
+                                let _ = line_sender.send(CommandExecInfo::End {
+                                    status: None,
+                                    usage: None,
+                                });
                             }
-                            break;
-                        }
-                        Ok(_) => {
-                            let response = CommandExecInfo::Line(CommandOutputLine {
-                                content: TLine::from_tty(&line),
-                                origin: CommandStream::StdErr,
-                            });
-                            if err_line_sender.send(response).is_err() {
-                                break; // channel closed
+                            Err(e) => {
+                                warn!("failed to get the status of the command: {e}");
                             }
+                        },
+                        StopMessage::Kill => {
+                            debug!("explicit interrupt received");
+                            kill(&job_kill, grace_period, &mut child);
                         }
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        let duration = start.elapsed();
+                        info!("command timed out after {duration:?}");
+                        kill(&job_kill, grace_period, &mut child);
+                        let _ = line_sender.send(CommandExecInfo::TimedOut { duration });
                     }
-                    line.clear();
-                }
-            });
-
-            // now waiting for the stop event
-            match stop_receiver.recv() {
-                Ok(stop) => match stop {
-                    StopMessage::SendStatus => {
-                        let status = child.try_wait();
-                        if let Ok(status) = status {
-                            let _ = line_sender.send(CommandExecInfo::End { status });
-                        }
+                    Err(e) => {
+                        debug!("recv error: {e}"); // probably just the executor dropped
+                        kill(&job_kill, grace_period, &mut child);
                     }
-                    StopMessage::Kill => {
-                        debug!("explicit interrupt received");
-                        kill(kill_command.as_deref(), &mut child);
+                }
+                if !reaped {
+                    if let Err(e) = child.wait() {
+                        warn!("waiting for child failed: {e}");
                     }
-                },
-                Err(e) => {
-                    debug!("recv error: {e}"); // probably just the executor dropped
-                    kill(kill_command.as_deref(), &mut child);
                 }
-            }
-            if let Err(e) = child.wait() {
-                warn!("waiting for child failed: {e}");
+                return;
             }
         });
         Ok(TaskExecutor {
             child_thread,
             stop_sender,
+            killed,
+            stdin,
         })
     }
 }
 
-/// kill the child process, either by using a specific command or by
-/// using the default platform kill method if the specific command
-/// failed or wasn't provided.
-fn kill(
-    kill_command: Option<&[String]>,
+impl StageLauncher {
+    fn stage_count(&self) -> usize {
+        self.commands.lock().unwrap().len()
+    }
+    /// Launch the command of a stage
+    fn spawn(
+        &self,
+        stage: usize,
+        task: Task,
+    ) -> Result<RunningStage> {
+        let mut commands = self.commands.lock().unwrap();
+        let command = &mut commands[stage];
+        let mut pty = None;
+        let mut pipe_reader = None;
+        if self.pty {
+            let (width, height) = terminal::size().unwrap_or((80, 24));
+            let opened = Pty::open(width, height).context("failed to open a pseudo-terminal")?;
+            opened.attach(command)?;
+            pty = Some(opened);
+        } else if self.merge_streams {
+            let (reader, writer) = open_pipe().context("failed to open a pipe")?;
+            command
+                .stdout(Stdio::from(writer.try_clone()?))
+                .stderr(Stdio::from(writer));
+            pipe_reader = Some(reader);
+        }
+        let child = command
+            .env("RUST_BACKTRACE", if task.backtrace { "1" } else { "0" })
+            .spawn();
+        if pty.is_some() {
+            detach_pty(command);
+        } else if pipe_reader.is_some() {
+            // the writing end must only be open in the child, so
+            // that the end of the output is read
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        let mut child = child.context("failed to launch command")?;
+        // only the master side is kept, so that the end of the command
+        // closes the pseudo-terminal
+        let pty_master = pty.map(|pty| pty.master);
+        let stdin = match &pty_master {
+            Some(master) => Some(JobStdin::Pty(master.try_clone()?)),
+            None => child.stdin.take().map(JobStdin::Pipe),
+        };
+        Ok(RunningStage {
+            child,
+            stdin,
+            merged_output: pty_master.or(pipe_reader),
+        })
+    }
+}
+
+/// Numbers and dates the lines read on the outputs of a task
+#[derive(Clone)]
+struct LineStamper {
+    start: Instant,
+    next_seq: Arc<AtomicUsize>,
+    stage: usize,
+    /// whether the task was killed, its lines being then useless
+    killed: Arc<AtomicBool>,
+}
+
+impl LineStamper {
+    fn new(
+        start: Instant,
+        killed: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            start,
+            next_seq: Arc::new(AtomicUsize::new(0)),
+            stage: 0,
+            killed,
+        }
+    }
+    /// Whether the task was killed, so that the lines written by its
+    /// processes during the grace period must not be sent, as they
+    /// would be mixed with the ones of the next task
+    fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+    /// Return a stamper for the lines of a stage of the task, the
+    /// numbering going on
+    fn for_stage(
+        &self,
+        stage: usize,
+    ) -> Self {
+        Self {
+            stage,
+            ..self.clone()
+        }
+    }
+    /// Build the message of a line which was just read
+    fn line(
+        &self,
+        content: TLine,
+        origin: CommandStream,
+    ) -> CommandExecInfo {
+        CommandExecInfo::Line(CommandOutputLine {
+            content,
+            origin,
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            time: self.start.elapsed(),
+            stage: self.stage,
+        })
+    }
+}
+
+/// Open a pipe, whose ends aren't inherited by other commands, and
+/// return its reading and writing ends
+#[cfg(unix)]
+fn open_pipe() -> io::Result<(File, File)> {
+    use std::os::fd::FromRawFd;
+    let mut fds = [0; 2];
+    // SAFETY: the file descriptors are checked before being owned by the Files
+    unsafe {
+        #[cfg(target_os = "linux")]
+        let res = libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC);
+        #[cfg(not(target_os = "linux"))]
+        let res = libc::pipe(fds.as_mut_ptr());
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let files = (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]));
+        #[cfg(not(target_os = "linux"))]
+        for fd in fds {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(not(unix))]
+fn open_pipe() -> io::Result<(File, File)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "merged streams are only supported on unix",
+    ))
+}
+
+/// Start the thread sending the lines read on the stream where both stdout
+/// and stderr are written (the master side of the pseudo-terminal, or a
+/// pipe), until the program ends (then ask the child_thread to send status)
+fn pipe_merged_output(
+    reader: File,
+    stamper: LineStamper,
+    line_sender: &Sender<CommandExecInfo>,
+    stop_sender: Sender<StopMessage>,
+) {
+    let merged_line_sender = line_sender.clone();
+    let mut buf_reader = BufReader::new(reader);
+    thread::spawn(move || {
+        let mut line = Vec::new();
+        loop {
+            match buf_reader.read_until(b'\n', &mut line) {
+                Ok(0) => {
+                    if let Err(e) = stop_sender.send(StopMessage::SendStatus) {
+                        warn!("sending stop message failed: {e}");
+                    }
+                    break;
+                }
+                Err(e) if is_end_of_output(&e) => {
+                    if let Err(e) = stop_sender.send(StopMessage::SendStatus) {
+                        warn!("sending stop message failed: {e}");
+                    }
+                    break;
+                }
+                Err(e) => {
+                    warn!("error : {e}");
+                }
+                Ok(_) if stamper.is_killed() => {
+                    break;
+                }
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&line);
+                    let content = TLine::from_tty(pty_line_content(&line));
+                    let response = stamper.line(content, CommandStream::StdOut);
+                    if merged_line_sender.send(response).is_err() {
+                        break; // channel closed
+                    }
+                }
+            }
+            line.clear();
+        }
+    });
+}
+
+/// Tell whether a read error only means there's nothing more to read: on
+/// a pseudo-terminal, reading the master side fails with EIO once the
+/// command and its children closed the slave side
+fn is_end_of_output(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    if e.raw_os_error() == Some(libc::EIO) {
+        return true;
+    }
+    e.kind() == io::ErrorKind::Other
+}
+
+/// Start the threads sending the lines of the piped stdout (if needed)
+/// and stderr of the child, the end of stderr meaning the end of the program
+fn pipe_output(
     child: &mut Child,
+    with_stdout: bool,
+    stamper: LineStamper,
+    line_sender: &Sender<CommandExecInfo>,
+    err_stop_sender: Sender<StopMessage>,
 ) {
-    if let Some(kill_command) = kill_command {
-        info!("launch specific kill command {kill_command:?}");
-        let Err(e) = run_kill_command(kill_command, child) else {
+    // thread piping the stdout lines
+    if with_stdout {
+        let sender = line_sender.clone();
+        let stamper = stamper.clone();
+        let Some(stdout) = child.stdout.take() else {
+            warn!("process has no stdout"); // unlikely
             return;
         };
-        warn!("specific kill command failed: {e}");
+        let mut buf_reader = BufReader::new(stdout);
+        thread::spawn(move || {
+            let mut line = Vec::new();
+            loop {
+                match buf_reader.read_until(b'\n', &mut line) {
+                    Err(e) => {
+                        warn!("error : {e}");
+                    }
+                    Ok(0) => {
+                        // there won't be anything more, quitting
+                        break;
+                    }
+                    Ok(_) if stamper.is_killed() => {
+                        break;
+                    }
+                    Ok(_) => {
+                        let content = TLine::from_tty(&String::from_utf8_lossy(&line));
+                        let response = stamper.line(content, CommandStream::StdOut);
+                        if sender.send(response).is_err() {
+                            break; // channel closed
+                        }
+                    }
+                }
+                line.clear();
+            }
+        });
     }
+
+    // starting a thread to handle stderr lines until program
+    // ends (then ask the child_thread to send status)
+    let err_line_sender = line_sender.clone();
+    let stderr = child.stderr.take().expect("child missing stderr");
+    let mut buf_reader = BufReader::new(stderr);
+    thread::spawn(move || {
+        let mut line = Vec::new();
+        loop {
+            match buf_reader.read_until(b'\n', &mut line) {
+                Err(e) => {
+                    warn!("error : {e}");
+                }
+                Ok(0) => {
+                    if let Err(e) = err_stop_sender.send(StopMessage::SendStatus) {
+                        warn!("sending stop message failed: {e}");
+                    }
+                    break;
+                }
+                Ok(_) if stamper.is_killed() => {
+                    break;
+                }
+                Ok(_) => {
+                    let content = TLine::from_tty(&String::from_utf8_lossy(&line));
+                    let response = stamper.line(content, CommandStream::StdErr);
+                    if err_line_sender.send(response).is_err() {
+                        break; // channel closed
+                    }
+                }
+            }
+            line.clear();
+        }
+    });
+}
+
+/// Return the exit status of the child and the resources it used,
+/// once it's finished, which should be imminent as its outputs are
+/// closed (but a process may close them and go on, so there's a limit)
+fn wait_ended(
+    child: &mut Child,
+    start: Instant,
+) -> io::Result<Option<(ExitStatus, TaskUsage)>> {
+    for _ in 0..50 {
+        if let Some(ended) = try_wait_with_usage(child, start)? {
+            return Ok(Some(ended));
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    Ok(None)
+}
+
+/// Return the exit status of the child and the resources it used,
+/// if it's finished.
+///
+/// The child is then reaped, it must not be waited for anymore.
+#[cfg(unix)]
+fn try_wait_with_usage(
+    child: &mut Child,
+    start: Instant,
+) -> io::Result<Option<(ExitStatus, TaskUsage)>> {
+    use std::os::unix::process::ExitStatusExt;
+    let mut status = 0;
+    // SAFETY: rusage is a plain C struct, filled by wait4
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = unsafe {
+        libc::wait4(
+            child.id() as libc::pid_t,
+            &mut status,
+            libc::WNOHANG,
+            &mut rusage,
+        )
+    };
+    match pid {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(None), // still running
+        _ => {
+            let seconds = |tv: libc::timeval| tv.tv_sec as f64 + tv.tv_usec as f64 / 1_000_000.0;
+            // ru_maxrss is in kilobytes on Linux, in bytes on macOS
+            let rss_unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+            let usage = TaskUsage {
+                wall_time: start.elapsed().as_secs_f64(),
+                cpu_time: Some(seconds(rusage.ru_utime) + seconds(rusage.ru_stime)),
+                max_rss: Some(rusage.ru_maxrss as u64 * rss_unit),
+            };
+            Ok(Some((ExitStatus::from_raw(status), usage)))
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn try_wait_with_usage(
+    child: &mut Child,
+    start: Instant,
+) -> io::Result<Option<(ExitStatus, TaskUsage)>> {
+    let usage = TaskUsage {
+        wall_time: start.elapsed().as_secs_f64(),
+        cpu_time: None,
+        max_rss: None,
+    };
+    Ok(child.try_wait()?.map(|status| (status, usage)))
+}
+
+/// kill the child process, either by using a specific command or by
+/// signaling its process group, or by using the default platform kill
+/// method if the former failed
+fn kill(
+    kill: &Kill,
+    grace_period: Duration,
+    child: &mut Child,
+) {
+    let res = match kill {
+        Kill::Command(kill_command) => {
+            info!("launch specific kill command {kill_command:?}");
+            run_kill_command(kill_command, child)
+        }
+        Kill::Signal(signal) => kill_group(*signal, grace_period, child),
+    };
+    let Err(e) = res else {
+        return;
+    };
+    warn!("specific kill failed: {e}");
     child.kill().expect("command couldn't be killed")
 }
 
+/// Send the signal to the process group of the child, then, if some
+/// processes are still alive after the grace period, send them SIGKILL
+#[cfg(unix)]
+fn kill_group(
+    signal: Signal,
+    grace_period: Duration,
+    child: &mut Child,
+) -> io::Result<()> {
+    // the child is the leader of its own process group
+    let pgid = child.id() as libc::pid_t;
+    let signal_group = |signal: libc::c_int| -> io::Result<()> {
+        if unsafe { libc::killpg(pgid, signal) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    info!("sending {signal:?} to process group {pgid}");
+    if let Err(e) = signal_group(signal.number()) {
+        if e.raw_os_error() == Some(libc::ESRCH) {
+            return Ok(()); // all processes already ended
+        }
+        return Err(e);
+    }
+    let start = Instant::now();
+    loop {
+        // the group lives as long as one of its processes, even when
+        // the leader ended (and was reaped by try_wait)
+        if child.try_wait()?.is_some() && signal_group(0).is_err() {
+            return Ok(());
+        }
+        if start.elapsed() >= grace_period {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    info!("processes still alive after the grace period, sending SIGKILL");
+    match signal_group(libc::SIGKILL) {
+        Err(e) if e.raw_os_error() != Some(libc::ESRCH) => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Kill the child, as there's no signal out of unix
+#[cfg(not(unix))]
+fn kill_group(
+    signal: Signal,
+    _grace_period: Duration,
+    child: &mut Child,
+) -> io::Result<()> {
+    if Kill::Signal(signal) != Kill::default() {
+        warn!("signals are only supported on unix, the command is just killed");
+    }
+    child.kill()
+}
+
 fn run_kill_command(
     kill_command: &[String],
     child: &mut Child,
@@ -242,10 +816,9 @@ fn run_kill_command(
     let mut proc = kill.spawn()?;
     let status = proc.wait()?;
     if !status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("kill command returned nonzero status: {status}"),
-        ));
+        return Err(io::Error::other(format!(
+            "kill command returned nonzero status: {status}"
+        )));
     }
     child.wait()?;
     Ok(())
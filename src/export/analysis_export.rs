@@ -18,10 +18,13 @@ pub struct LineAnalysisExport {
 }
 
 impl AnalysisExport {
-    pub fn build(cmd_output_lines: &[CommandOutputLine]) -> Self {
+    pub fn build(
+        cmd_output_lines: &[CommandOutputLine],
+        analyzer: &dyn Analyzer,
+    ) -> Self {
         let mut lines = Vec::new();
         for line in cmd_output_lines {
-            let analysis = LineAnalysis::from(line);
+            let analysis = analyzer.analyze_line(line);
             lines.push(LineAnalysisExport {
                 line: line.clone(),
                 analysis,
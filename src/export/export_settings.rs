@@ -31,7 +31,7 @@ impl ExportSettings {
         info!("exporting to {:?}", path);
         match self.exporter {
             Exporter::Analysis => {
-                let analysis_export = AnalysisExport::build(&report.output.lines);
+                let analysis_export = AnalysisExport::build(&report.output.lines, state.analyzer());
                 let json = serde_json::to_string_pretty(&analysis_export)?;
                 std::fs::write(&path, json)?;
             }
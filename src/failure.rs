@@ -4,11 +4,27 @@ use {
         Deserialize,
         Serialize,
     },
+    std::time::Duration,
 };
 
 /// data of a failed command
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Failure {
-    pub error_code: i32,
+    /// the exit code of the command, unknown when the failure is
+    /// the one of its execution (eg a command which couldn't be launched)
+    pub error_code: Option<i32>,
+    pub output: CommandOutput,
+    /// the resources used by the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TaskUsage>,
+}
+
+/// data of a command killed because it didn't end before the
+/// timeout of its job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedOut {
+    /// how long the command ran before being killed
+    pub duration: Duration,
+    /// the output captured until the command was killed
     pub output: CommandOutput,
 }
@@ -0,0 +1,177 @@
+use {
+    crate::*,
+    serde::Deserialize,
+    std::collections::HashSet,
+};
+
+/// How the items of a report are organized when displayed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grouping {
+    /// a flat list of items
+    #[default]
+    None,
+    /// items grouped by the file of their primary location
+    File,
+    /// items grouped by the package containing their primary location
+    Package,
+}
+
+/// A group of items of a report, sharing the same file or package
+#[derive(Debug)]
+struct ItemGroup {
+    name: String,
+    item_idxs: Vec<usize>,
+    errors: usize,
+    test_fails: usize,
+    warnings: usize,
+    benches: usize,
+}
+
+impl Grouping {
+    /// Return the grouping mode following this one, when cycling
+    pub fn next(self) -> Self {
+        match self {
+            Self::None => Self::File,
+            Self::File => Self::Package,
+            Self::Package => Self::None,
+        }
+    }
+    /// Return the name of the group of an item, prefixed with the
+    /// stage of the item when the job is a pipeline
+    pub fn group_name(
+        self,
+        item: &Item,
+        mission: &Mission,
+    ) -> String {
+        let name = match (self, &item.location) {
+            (Self::None, _) => String::new(),
+            (_, None) => "(no location)".to_string(),
+            (Self::File, Some(location)) => location.path.clone(),
+            (Self::Package, Some(location)) => {
+                let path = mission.workspace_root.join(&location.path);
+                mission
+                    .packages
+                    .iter()
+                    .filter(|(_, dir)| path.starts_with(dir))
+                    .max_by_key(|(_, dir)| dir.components().count())
+                    .map_or_else(|| "(other)".to_string(), |(name, _)| name.clone())
+            }
+        };
+        match &item.stage {
+            Some(stage) if name.is_empty() => stage.clone(),
+            Some(stage) => format!("{stage} › {name}"),
+            None => name,
+        }
+    }
+    /// Build the report to display, with the items grouped under headers
+    /// and the items of the collapsed groups hidden.
+    ///
+    /// The order of the groups is the one of their first items.
+    pub fn group(
+        self,
+        report: &Report,
+        mission: &Mission,
+        collapsed_groups: &HashSet<String>,
+    ) -> Report {
+        let mut groups: Vec<ItemGroup> = Vec::new();
+        let mut seen_item_idxs = HashSet::new();
+        for line in &report.lines {
+            if !seen_item_idxs.insert(line.item_idx) {
+                continue;
+            }
+            let Some(item) = report.item(line.item_idx) else {
+                continue;
+            };
+            let name = self.group_name(item, mission);
+            let group = match groups.iter().position(|group| group.name == name) {
+                Some(idx) => &mut groups[idx],
+                None => {
+                    groups.push(ItemGroup {
+                        name,
+                        item_idxs: Vec::new(),
+                        errors: 0,
+                        test_fails: 0,
+                        warnings: 0,
+                        benches: 0,
+                    });
+                    groups.last_mut().unwrap()
+                }
+            };
+            group.item_idxs.push(item.idx);
+            match item.kind {
+                Kind::TestFail => group.test_fails += 1,
+                Kind::Warning => group.warnings += 1,
+                Kind::Bench => group.benches += 1,
+                Kind::Sum => {}
+                _ => group.errors += 1,
+            }
+        }
+        let mut lines = Vec::new();
+        for group in &groups {
+            let collapsed = collapsed_groups.contains(&group.name);
+            lines.push(Line {
+                // the header belongs to the first item of its group, so
+                // that it's scrolled to with this item
+                item_idx: group.item_idxs[0],
+                line_type: LineType::GroupHeader,
+                content: group.header(collapsed),
+            });
+            if collapsed {
+                continue;
+            }
+            lines.extend(
+                report
+                    .lines
+                    .iter()
+                    .filter(|line| group.item_idxs.contains(&line.item_idx))
+                    .cloned(),
+            );
+        }
+        let stats = Stats::from(&lines);
+        Report {
+            lines,
+            items: report.items.clone(),
+            stats,
+            suggest_backtrace: report.suggest_backtrace,
+            output: CommandOutput::default(),
+            usage: report.usage,
+        }
+    }
+}
+
+impl ItemGroup {
+    /// Build the header line of the group, with its counts of items,
+    /// for example "▼ src/main.rs (2 errors, 1 warning)"
+    fn header(
+        &self,
+        collapsed: bool,
+    ) -> TLine {
+        let mut counts = Vec::new();
+        for (count, name) in [
+            (self.errors, "error"),
+            (self.test_fails, "fail"),
+            (self.warnings, "warning"),
+            (self.benches, "bench"),
+        ] {
+            match count {
+                0 => {}
+                1 => counts.push(format!("1 {name}")),
+                _ => counts.push(format!("{count} {name}s")),
+            }
+        }
+        let arrow = if collapsed { '▶' } else { '▼' };
+        TLine {
+            strings: vec![
+                TString {
+                    csi: CSI_BOLD.to_string(),
+                    raw: format!("{arrow} {}", self.name),
+                },
+                TString {
+                    csi: CSI_GREY.to_string(),
+                    raw: format!(" ({})", counts.join(", ")),
+                },
+            ],
+        }
+    }
+}
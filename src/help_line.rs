@@ -6,10 +6,13 @@ pub struct HelpLine {
     wrap: Option<String>,
     not_wrap: Option<String>,
     toggle_backtrace: Option<String>,
+    toggle_group: Option<String>,
     help: Option<String>,
     close_help: Option<String>,
     pause: Option<String>,
     unpause: Option<String>,
+    input: Option<String>,
+    next_tab: Option<String>,
 }
 
 impl HelpLine {
@@ -31,6 +34,9 @@ impl HelpLine {
         let toggle_backtrace = kb
             .shortest_internal_key(Internal::ToggleBacktrace)
             .map(|k| format!("*{k}* to toggle backtraces"));
+        let toggle_group = kb
+            .shortest_internal_key(Internal::ToggleGroup)
+            .map(|k| format!("*{k}* to collapse or expand the group"));
         let help = kb
             .shortest_internal_key(Internal::Help)
             .map(|k| format!("*{k}* for help"));
@@ -46,16 +52,25 @@ impl HelpLine {
             .shortest_internal_key(Internal::Unpause)
             .or(kb.shortest_internal_key(Internal::TogglePause))
             .map(|k| format!("*{k}* to unpause"));
+        let input = kb
+            .shortest_internal_key(Internal::ToggleInput)
+            .map(|k| format!("*{k}* to type in the job's input"));
+        let next_tab = kb
+            .shortest_internal_key(Internal::NextTab)
+            .map(|k| format!("*{k}* to switch tab"));
         Self {
             quit,
             toggle_summary,
             wrap,
             not_wrap,
             toggle_backtrace,
+            toggle_group,
             help,
             close_help,
             pause,
             unpause,
+            input,
+            next_tab,
         }
     }
     pub fn markdown(
@@ -63,17 +78,38 @@ impl HelpLine {
         state: &AppState,
     ) -> String {
         let mut parts: Vec<&str> = vec![&self.quit];
+        let test_summary;
         if state.is_help() {
             if let Some(s) = &self.close_help {
                 parts.push(s);
             }
         } else {
+            // on a pseudo-terminal, the command always has an input
+            if state.is_computing() && (state.mission.need_stdin() || state.mission.use_pty()) {
+                if let Some(s) = &self.input {
+                    parts.push(s);
+                }
+            }
+            if state.tab_bar.is_some() {
+                if let Some(s) = &self.next_tab {
+                    parts.push(s);
+                }
+            }
             if state.auto_refresh.is_paused() {
                 if let Some(s) = &self.unpause {
                     parts.push(s);
                 }
             }
             if let CommandResult::Report(report) = &state.cmd_result {
+                if let Some(duration) = report.stats.test_duration {
+                    test_summary = match report.stats.measured_tests {
+                        0 => format!("tests ran in *{duration:.2}s*"),
+                        measured => {
+                            format!("tests ran in *{duration:.2}s* (*{measured}* measured)")
+                        }
+                    };
+                    parts.push(&test_summary);
+                }
                 if report.suggest_backtrace {
                     if let Some(s) = &self.toggle_backtrace {
                         parts.push(s);
@@ -82,6 +118,11 @@ impl HelpLine {
                     if let Some(s) = &self.toggle_summary {
                         parts.push(s);
                     }
+                    if state.is_grouping() {
+                        if let Some(s) = &self.toggle_group {
+                            parts.push(s);
+                        }
+                    }
                 }
             }
             if state.wrap {
@@ -8,12 +8,18 @@ use {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Internal {
     Back,
+    CycleGrouping,
     Help,
+    NextTab,
+    PreviousTab,
     Quit,
     Refresh, // clear and rerun
     ReRun,
     Scroll(ScrollCommand),
+    ToggleAllGroups,
     ToggleBacktrace,
+    ToggleGroup,
+    ToggleInput,
     ToggleRawOutput,
     ToggleSummary,
     ToggleWrap,
@@ -29,12 +35,18 @@ impl fmt::Display for Internal {
     ) -> fmt::Result {
         match self {
             Self::Back => write!(f, "back to previous page or job"),
+            Self::CycleGrouping => write!(f, "change grouping (none, file, package)"),
             Self::Help => write!(f, "help"),
+            Self::NextTab => write!(f, "show the next tab"),
+            Self::PreviousTab => write!(f, "show the previous tab"),
             Self::Quit => write!(f, "quit"),
             Self::Refresh => write!(f, "clear then run current job again"),
             Self::ReRun => write!(f, "run current job again"),
             Self::Scroll(scroll_command) => scroll_command.fmt(f),
+            Self::ToggleAllGroups => write!(f, "collapse or expand all groups"),
             Self::ToggleBacktrace => write!(f, "toggle backtrace"),
+            Self::ToggleGroup => write!(f, "collapse or expand the group on top"),
+            Self::ToggleInput => write!(f, "type in the input of the job"),
             Self::ToggleRawOutput => write!(f, "toggle raw output"),
             Self::ToggleSummary => write!(f, "toggle summary"),
             Self::ToggleWrap => write!(f, "toggle wrap"),
@@ -53,12 +65,18 @@ impl std::str::FromStr for Internal {
         }
         match s {
             "back" => Ok(Self::Back),
+            "cycle-grouping" => Ok(Self::CycleGrouping),
             "help" => Ok(Self::Help),
+            "next-tab" => Ok(Self::NextTab),
+            "previous-tab" => Ok(Self::PreviousTab),
             "quit" => Ok(Self::Quit),
             "refresh" => Ok(Self::Refresh),
             "rerun" => Ok(Self::ReRun),
-            "toggle-raw-output" => Ok(Self::ToggleRawOutput),
+            "toggle-all-groups" => Ok(Self::ToggleAllGroups),
             "toggle-backtrace" => Ok(Self::ToggleBacktrace),
+            "toggle-group" => Ok(Self::ToggleGroup),
+            "toggle-input" => Ok(Self::ToggleInput),
+            "toggle-raw-output" => Ok(Self::ToggleRawOutput),
             "toggle-summary" => Ok(Self::ToggleSummary),
             "toggle-wrap" => Ok(Self::ToggleWrap),
             "pause" => Ok(Self::Pause),
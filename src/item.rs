@@ -0,0 +1,423 @@
+use {
+    crate::*,
+    lazy_regex::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::path::Path,
+};
+
+/// An item of a report: an error, a warning, or a test failure.
+///
+/// Its lines are the report lines having its index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
+    /// the index of the item, the same as the item_idx of its lines
+    pub idx: usize,
+    pub kind: Kind,
+    /// the message, as given in the title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// the code of the error (eg "E0308") or the name of the lint
+    /// (eg "clippy::needless_borrow") when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// the place of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// other places related to the problem, for example the definition
+    /// of a called function, or the invocation of a macro
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_locations: Vec<Location>,
+    /// the number of times the item was reported, for example once
+    /// per target when compiling both a library and its tests
+    pub targets: usize,
+    /// the result, when the item is a benchmark
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench: Option<Bench>,
+    /// the helps of the diagnostic, for example "consider borrowing here"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub helps: Vec<String>,
+    /// the label of the stage of the job which reported the item,
+    /// when the job is a pipeline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+}
+
+impl Item {
+    /// Build the items of lines whose item indexes are already assigned
+    pub fn from_lines(lines: &[Line]) -> Vec<Item> {
+        let mut items: Vec<Item> = Vec::new();
+        // the message of a note, which may label the location following it
+        let mut note = None;
+        // whether the last location may still be labelled by a marker line
+        let mut label_pending = false;
+        // whether the next line is the message of a panic
+        let mut panic_message_pending = false;
+        for line in lines {
+            if let LineType::Title(kind) = line.line_type {
+                items.push(Item {
+                    idx: line.item_idx,
+                    kind,
+                    message: line.title_message().map(|message| message.to_string()),
+                    code: line.content.code().map(|code| code.to_string()),
+                    location: None,
+                    secondary_locations: Vec::new(),
+                    targets: 1,
+                    bench: None,
+                    helps: Vec::new(),
+                    stage: None,
+                });
+                note = None;
+                label_pending = false;
+                panic_message_pending = false;
+                continue;
+            }
+            let Some(item) = items.last_mut().filter(|item| item.idx == line.item_idx) else {
+                continue;
+            };
+            let raw = line.content.to_raw();
+            if line.line_type == LineType::Help {
+                if let Some((_, help)) = regex_captures!(r"^\s*(?:= )?help: (.+)$", &raw) {
+                    item.helps.push(help.to_string());
+                }
+            }
+            if matches!(item.kind, Kind::TestFail | Kind::BuildScript) {
+                if let Some((mut location, message)) = as_panic(&raw) {
+                    if item.kind == Kind::BuildScript {
+                        // the path is relative to the package of the build script
+                        if let Some(dir) =
+                            item.message.as_deref().and_then(build_script_package_dir)
+                        {
+                            location.path = Path::new(dir)
+                                .join(&location.path)
+                                .to_string_lossy()
+                                .to_string();
+                        }
+                    }
+                    // the panic locates the failure better than the test
+                    item.set_primary_location(location);
+                    panic_message_pending = message.is_none();
+                    if message.is_some() {
+                        item.message = message;
+                    }
+                    continue;
+                }
+                if panic_message_pending && !line.content.is_blank() {
+                    item.message = Some(raw.trim().to_string());
+                    panic_message_pending = false;
+                    continue;
+                }
+            }
+            if item.kind == Kind::Link {
+                if let Some(symbol) = as_undefined_symbol(&raw) {
+                    // the message of the linker is more useful than "linking with `cc` failed"
+                    item.message = Some(format!("undefined symbol: {symbol}"));
+                    continue;
+                }
+                if let Some((_, location)) =
+                    regex_captures!(r"^\s*>>> referenced by \S+ \(([^\s()]+:\d+)\)$", &raw)
+                {
+                    item.add_location(Location::parse(location));
+                    continue;
+                }
+            }
+            let location = line
+                .location()
+                .or_else(|| regex_captures!(r"^\s*(?:-->|:::) (\S+)$", &raw).map(|(_, l)| l));
+            if let Some(location) = location {
+                let mut location = Location::parse(location);
+                location.label = note.take();
+                let unlabelled = location.label.is_none();
+                label_pending = item.add_location(location) && unlabelled;
+                continue;
+            }
+            if label_pending {
+                if let Some((_, label)) =
+                    regex_captures!(r"^\s*\d*\s*\|[\s|]*(?:[\^-]+\s+)+([^\s|^-].*)$", &raw)
+                {
+                    item.label_last_location(label);
+                    label_pending = false;
+                }
+            }
+            note = regex_captures!(r"^\s*(?:= )?note: (.+)$", &raw)
+                .map(|(_, message)| message.trim().to_string());
+            if item.code.is_none() && line.line_type.is_detail() {
+                item.code = lint_name(&raw);
+            }
+        }
+        items
+    }
+    /// Tell whether both items are about the same problem, which
+    /// happens when the same code is compiled for several targets
+    pub fn is_same_as(
+        &self,
+        other: &Item,
+    ) -> bool {
+        // a test failure is about its test, even if it shares its
+        // panic with other ones
+        self.kind != Kind::TestFail
+            && self.kind == other.kind
+            && self.message == other.message
+            && match (&self.location, &other.location) {
+                (Some(a), Some(b)) => a.same_place(b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+    /// Add a location, the first one being the primary one.
+    ///
+    /// Return false when the place was already known
+    fn add_location(
+        &mut self,
+        location: Location,
+    ) -> bool {
+        if self.location.is_none() {
+            self.location = Some(location);
+            return true;
+        }
+        if self.locations().any(|l| l.same_place(&location)) {
+            return false;
+        }
+        self.secondary_locations.push(location);
+        true
+    }
+    /// Make the location the primary one, the previous primary one
+    /// becoming the first secondary one
+    fn set_primary_location(
+        &mut self,
+        location: Location,
+    ) {
+        self.secondary_locations
+            .retain(|l| !l.same_place(&location));
+        if let Some(previous) = self.location.replace(location) {
+            if !self.locations().any(|l| l.same_place(&previous)) {
+                self.secondary_locations.insert(0, previous);
+            }
+        }
+    }
+    fn label_last_location(
+        &mut self,
+        label: &str,
+    ) {
+        let location = match self.secondary_locations.last_mut() {
+            Some(location) => Some(location),
+            None => self.location.as_mut(),
+        };
+        if let Some(location) = location {
+            location.label = Some(label.trim().to_string());
+        }
+    }
+    /// Return the primary location then the secondary ones
+    pub fn locations(&self) -> impl Iterator<Item = &Location> {
+        self.location.iter().chain(&self.secondary_locations)
+    }
+}
+
+/// Return the location and, in the old format, the message of a panic
+/// when the line tells it, for example
+///
+///  "thread 'tests::failing' panicked at src/lib.rs:12:9:"
+/// or
+///  "thread 'tests::failing' panicked at 'assertion failed: false', src/lib.rs:12:9"
+fn as_panic(raw: &str) -> Option<(Location, Option<String>)> {
+    if let Some((_, location)) = regex_captures!(
+        r#"^\s*thread '.+?'(?: \(\d+\))? panicked at ([^\s'"]+:\d+(?::\d+)?):$"#,
+        raw
+    ) {
+        return Some((Location::parse(location), None));
+    }
+    regex_captures!(
+        r#"^\s*thread '.+?'(?: \(\d+\))? panicked at '(.*)', ([^\s'"]+:\d+(?::\d+)?)$"#,
+        raw
+    )
+    .map(|(_, message, location)| (Location::parse(location), Some(message.to_string())))
+}
+
+/// Return the directory of the package of a failing build script, given
+/// the message "failed to run custom build command for `bs v0.1.0 (/home/me/bs)`"
+fn build_script_package_dir(message: &str) -> Option<&str> {
+    regex_captures!(
+        r"^failed to run custom build command for `\S+ \S+ \((.+)\)`$",
+        message
+    )
+    .map(|(_, dir)| dir)
+}
+
+/// Return the symbol the linker didn't find, when the line tells it, for example
+///
+///  "  = note: rust-lld: error: undefined symbol: missing_fn"
+/// or
+///  "          /usr/bin/ld: main.o: in function `main': undefined reference to `missing_fn'"
+fn as_undefined_symbol(raw: &str) -> Option<&str> {
+    regex_captures!(r"undefined (?:symbol: |reference to `)([^\s'`]+)", raw)
+        .map(|(_, symbol)| symbol)
+}
+
+/// Return the name of the lint when the line tells it, for example
+///
+///  "  = note: `#[warn(unused_variables)]` on by default"
+/// or
+///  "  = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_borrow"
+fn lint_name(raw: &str) -> Option<String> {
+    if let Some((_, name)) = regex_captures!(r"rust-clippy/\S*#([a-z_\d]+)\b", raw) {
+        return Some(format!("clippy::{name}"));
+    }
+    regex_captures!(r"`#\[(?:warn|deny|forbid|expect)\(([\w:]+)\)\]`", raw)
+        .map(|(_, name)| name.to_string())
+}
+
+#[test]
+fn test_item_codes() {
+    let lines = vec![
+        TLine::title_with(CSI_BOLD_RED, "error[E0308]".to_string(), "mismatched types"),
+        TLine::location("src/main.rs:3:18".to_string()),
+        TLine::title(Kind::Warning, "unused variable: `x`"),
+        TLine::location("src/main.rs:2:9".to_string()),
+        TLine::from_raw("  = note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default".to_string()),
+        TLine::title(Kind::Warning, "needless borrow"),
+        TLine::location("src/main.rs:5:10".to_string()),
+        TLine::from_raw("  = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_borrow".to_string()),
+        TLine::from_raw("  = note: `#[warn(clippy::needless_borrow)]` on by default".to_string()),
+        TLine::title(Kind::Warning, "unused variable: `y`"),
+        TLine::location("src/main.rs:6:9".to_string()),
+    ];
+    let cmd_lines = CommandOutputLine::from_contents(lines, CommandStream::StdErr);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    let codes: Vec<Option<&str>> = report
+        .items
+        .iter()
+        .map(|item| item.code.as_deref())
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            Some("E0308"),
+            Some("unused_variables"),
+            Some("clippy::needless_borrow"),
+            None,
+        ]
+    );
+    assert_eq!(
+        report.items[2].helps,
+        vec![
+            "for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_borrow"
+        ]
+    );
+    // codes which rustc doesn't write in titles are added to them
+    let titles: Vec<String> = report
+        .lines
+        .iter()
+        .filter(|line| matches!(line.line_type, LineType::Title(_)))
+        .map(|line| line.content.to_raw())
+        .collect();
+    assert_eq!(titles[0], "error[E0308]: mismatched types");
+    assert_eq!(
+        titles[1],
+        "warning: unused variable: `x` [unused_variables]"
+    );
+}
+
+#[test]
+fn test_item_locations() {
+    let lines = vec![
+        TLine::title_with(
+            CSI_BOLD_RED,
+            "error[E0061]".to_string(),
+            "this function takes 1 argument but 0 arguments were supplied",
+        ),
+        TLine::location("src/main.rs:8:5".to_string()),
+        TLine::from_raw("  |".to_string()),
+        TLine::from_raw("8 |     foo();".to_string()),
+        TLine::from_raw("  |     --- ^^^-- argument #1 of type `u8` is missing".to_string()),
+        TLine::from_raw("  |".to_string()),
+        TLine::from_raw("note: function defined here".to_string()),
+        TLine::location("src/main.rs:1:4".to_string()),
+        TLine::from_raw("  |".to_string()),
+        TLine::from_raw("1 | fn foo(x: u8) {}".to_string()),
+        TLine::from_raw("  |    ^^^ -----".to_string()),
+        TLine::from_raw("  ::: src/util.rs:10:5".to_string()),
+        TLine::from_raw("   |".to_string()),
+        TLine::from_raw("10 |     call_foo!();".to_string()),
+        TLine::from_raw("   |     ----------- in this macro invocation".to_string()),
+    ];
+    let cmd_lines = CommandOutputLine::from_contents(lines, CommandStream::StdErr);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    let item = &report.items[0];
+    let location = item.location.as_ref().unwrap();
+    assert_eq!(location.to_string(), "src/main.rs:8:5");
+    assert_eq!(
+        location.label.as_deref(),
+        Some("argument #1 of type `u8` is missing")
+    );
+    let secondary: Vec<(String, Option<&str>)> = item
+        .secondary_locations
+        .iter()
+        .map(|l| (l.to_string(), l.label.as_deref()))
+        .collect();
+    assert_eq!(
+        secondary,
+        vec![
+            ("src/main.rs:1:4".to_string(), Some("function defined here")),
+            (
+                "src/util.rs:10:5".to_string(),
+                Some("in this macro invocation")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_test_fail_items() {
+    let output = r#"
+running 2 tests
+test tests::a ... FAILED
+test tests::b ... FAILED
+
+failures:
+
+---- tests::a stdout ----
+thread 'tests::a' (4321) panicked at src/lib.rs:12:9:
+assertion failed: false
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+---- tests::b stdout ----
+thread 'tests::b' panicked at 'boom', src/lib.rs:20:5
+
+
+failures:
+    tests::a
+    tests::b
+
+test result: FAILED. 0 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"#;
+    let cmd_lines = CommandOutputLine::from_tty_text(output, CommandStream::StdOut);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    let fails: Vec<(Option<&str>, String)> = report
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.message.as_deref(),
+                item.location.as_ref().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fails,
+        vec![
+            (
+                Some("assertion failed: false"),
+                "src/lib.rs:12:9".to_string()
+            ),
+            (Some("boom"), "src/lib.rs:20:5".to_string()),
+        ]
+    );
+}
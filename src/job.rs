@@ -18,6 +18,11 @@ pub struct Job {
     #[serde(default)]
     pub allow_warnings: bool,
 
+    /// The analyzer interpreting the output of the command, the
+    /// standard cargo one if not provided
+    #[serde(default)]
+    pub analyzer: AnalyzerRef,
+
     /// Whether gitignore rules must be applied
     pub apply_gitignore: Option<bool>,
 
@@ -32,12 +37,25 @@ pub struct Job {
 
     /// The tokens making the command to execute (first one
     /// is the executable).
-    /// This vector is guaranteed not empty
+    /// This vector is guaranteed not empty, unless there are steps,
     /// by the PackageConfig::from_path loader
+    #[serde(default)]
     pub command: Vec<String>,
 
-    /// A kill command. If not provided, SIGKILL is used.
-    pub kill: Option<Vec<String>>,
+    /// The commands of the stages of a pipeline, run in order, the
+    /// next one only when the previous one succeeded. Used instead
+    /// of `command`.
+    #[serde(default)]
+    pub steps: Vec<Vec<String>>,
+
+    /// How to interrupt the command: either the name of a signal
+    /// sent to its process group (SIGTERM if not provided), or a
+    /// kill command.
+    pub kill: Option<Kill>,
+
+    /// How long the processes are given to end after the kill signal,
+    /// before they're sent SIGKILL (1s if not provided)
+    pub grace_period: Option<ConfigDuration>,
 
     /// Whether to apply the default watch list, which is
     /// `["src", "tests", "benches", "examples"]`
@@ -48,6 +66,15 @@ pub struct Job {
     #[serde(default = "default_true")]
     pub default_watch: bool,
 
+    /// How long the command may run before being killed, its
+    /// result being then reported as timed out
+    pub timeout: Option<ConfigDuration>,
+
+    /// Rules classifying some lines of the output, applied
+    /// before the analyzer's own logic
+    #[serde(default)]
+    pub line_rules: Vec<LineRule>,
+
     /// Env vars to set for this job execution
     #[serde(default)]
     pub env: HashMap<String, String>,
@@ -56,6 +83,11 @@ pub struct Job {
     #[serde(default = "default_true")]
     pub expand_env_vars: bool,
 
+    /// Whether the command reads its standard input, which is
+    /// then typed in input mode (it's closed otherwise)
+    #[serde(default)]
+    pub need_stdin: bool,
+
     /// Whether we need to capture stdout too (stderr is
     /// always captured)
     #[serde(default)]
@@ -66,6 +98,17 @@ pub struct Job {
     #[serde(default)]
     pub on_success: Option<Action>,
 
+    /// Whether stdout and stderr are written in the same pipe, so that
+    /// their lines are read in the exact order they were written
+    #[serde(default)]
+    pub merge_streams: bool,
+
+    /// Whether to run the command on a pseudo-terminal (Linux only),
+    /// so that it behaves as when run in a shell. Stdout and stderr
+    /// are then both captured, merged.
+    #[serde(default)]
+    pub pty: bool,
+
     /// A list of directories that will be watched if the job
     /// is run on a package.
     /// src, examples, tests, and benches are implicitly included
@@ -110,19 +153,36 @@ impl Job {
         }
         Self {
             command,
+            steps: Vec::new(),
             kill: None,
+            grace_period: None,
+            timeout: None,
             default_watch: true,
             expand_env_vars: true,
             watch: Vec::new(),
+            need_stdin: false,
             need_stdout: false,
             on_success: None,
+            merge_streams: false,
+            pty: false,
             allow_warnings: false,
             allow_failures: false,
+            analyzer: AnalyzerRef::Standard,
             apply_gitignore: None,
             env: Default::default(),
+            line_rules: Vec::new(),
             background: true,
             extraneous_args: true,
             on_change_strategy: None,
         }
     }
+    /// The commands run in order by the job: its steps, or just its
+    /// command when it's not a pipeline
+    pub fn stages(&self) -> &[Vec<String>] {
+        if self.steps.is_empty() {
+            std::slice::from_ref(&self.command)
+        } else {
+            &self.steps
+        }
+    }
 }
@@ -0,0 +1,82 @@
+use termimad::crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+};
+
+/// The line being typed by the user in input mode, which is sent to
+/// the stdin of the job's command on enter
+#[derive(Debug, Default)]
+pub struct JobInput {
+    pub line: String,
+}
+
+/// What a key means when in input mode
+#[derive(Debug, PartialEq, Eq)]
+pub enum InputAction {
+    /// the line was edited, nothing to send yet
+    Edit,
+    /// send those bytes to the command
+    Send(String),
+    /// close the command's stdin (end of file)
+    Close,
+    /// leave input mode
+    Leave,
+    /// the key isn't handled by the input, its binding applies
+    Unhandled,
+}
+
+impl JobInput {
+    /// Apply a key to the input and return what must be done
+    pub fn apply_key(
+        &mut self,
+        key: KeyEvent,
+    ) -> InputAction {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => InputAction::Leave,
+            (KeyCode::Enter, _) => {
+                let mut line = std::mem::take(&mut self.line);
+                line.push('\n');
+                InputAction::Send(line)
+            }
+            (KeyCode::Backspace, _) => {
+                self.line.pop();
+                InputAction::Edit
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => InputAction::Close,
+            (KeyCode::Char(c), modifiers) if (modifiers - KeyModifiers::SHIFT).is_empty() => {
+                self.line.push(c);
+                InputAction::Edit
+            }
+            _ => InputAction::Unhandled,
+        }
+    }
+}
+
+#[test]
+fn test_job_input() {
+    let mut input = JobInput::default();
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    for c in "42x".chars() {
+        assert_eq!(input.apply_key(key(KeyCode::Char(c))), InputAction::Edit);
+    }
+    input.apply_key(key(KeyCode::Backspace));
+    assert_eq!(
+        input.apply_key(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::SHIFT)),
+        InputAction::Edit
+    );
+    assert_eq!(
+        input.apply_key(key(KeyCode::Enter)),
+        InputAction::Send("42!\n".to_string())
+    );
+    assert!(input.line.is_empty());
+    assert_eq!(
+        input.apply_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+        InputAction::Close
+    );
+    assert_eq!(
+        input.apply_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+        InputAction::Unhandled
+    );
+    assert_eq!(input.apply_key(key(KeyCode::Esc)), InputAction::Leave);
+}
@@ -7,10 +7,13 @@ use {
 };
 
 /// The stack of jobs that bacon ran, allowing
-/// to get back to the previous one
+/// to get back to the previous one.
+///
+/// An entry is usually one job, but it's several ones when
+/// they're run concurrently, in tabs.
 pub struct JobStack<'c> {
     settings: &'c Settings,
-    entries: Vec<ConcreteJobRef>,
+    entries: Vec<Vec<ConcreteJobRef>>,
 }
 
 impl<'c> JobStack<'c> {
@@ -21,44 +24,57 @@ impl<'c> JobStack<'c> {
         }
     }
 
-    fn initial_job(&self) -> &ConcreteJobRef {
-        self.settings
-            .arg_job
-            .as_ref()
-            .unwrap_or(&self.settings.default_job)
+    fn initial_jobs(&self) -> Vec<ConcreteJobRef> {
+        match &self.settings.arg_job {
+            Some(job) => vec![job.clone()],
+            None if !self.settings.tabs.is_empty() => self.settings.tabs.clone(),
+            None => vec![self.settings.default_job.clone()],
+        }
+    }
+
+    fn job(
+        &self,
+        concrete: &ConcreteJobRef,
+    ) -> Result<Job> {
+        Ok(match concrete {
+            ConcreteJobRef::Alias(alias) => Job::from_alias(alias, self.settings),
+            ConcreteJobRef::Name(name) => self
+                .settings
+                .jobs
+                .get(name)
+                .ok_or_else(|| anyhow!("job not found: {:?}", name))?
+                .clone(),
+        })
     }
 
-    pub fn pick_job(
+    /// Return the jobs to run, which are several ones when they're
+    /// run in tabs
+    pub fn pick_jobs(
         &mut self,
         job_ref: &JobRef,
-    ) -> Result<Option<(ConcreteJobRef, Job)>> {
+    ) -> Result<Option<Vec<(ConcreteJobRef, Job)>>> {
         debug!("picking job {job_ref:?}");
-        let concrete = match job_ref {
-            JobRef::Default => self.settings.default_job.clone(),
-            JobRef::Initial => self.initial_job().clone(),
+        let concretes = match job_ref {
+            JobRef::Default => vec![self.settings.default_job.clone()],
+            JobRef::Initial => self.initial_jobs(),
             JobRef::Previous => {
                 self.entries.pop();
                 match self.entries.pop() {
-                    Some(concrete) => concrete,
+                    Some(concretes) => concretes,
                     None => {
                         return Ok(None);
                     }
                 }
             }
-            JobRef::Concrete(concrete) => concrete.clone(),
-        };
-        let job = match &concrete {
-            ConcreteJobRef::Alias(alias) => Job::from_alias(alias, self.settings),
-            ConcreteJobRef::Name(name) => self
-                .settings
-                .jobs
-                .get(name)
-                .ok_or_else(|| anyhow!("job not found: {:?}", name))?
-                .clone(),
+            JobRef::Concrete(concrete) => vec![concrete.clone()],
         };
-        if self.entries.last() != Some(&concrete) {
-            self.entries.push(concrete.clone());
+        let jobs = concretes
+            .iter()
+            .map(|concrete| Ok((concrete.clone(), self.job(concrete)?)))
+            .collect::<Result<Vec<_>>>()?;
+        if self.entries.last() != Some(&concretes) {
+            self.entries.push(concretes);
         }
-        Ok(Some((concrete, job)))
+        Ok(Some(jobs))
     }
 }
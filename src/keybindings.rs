@@ -31,6 +31,9 @@ impl Default for KeyBindings {
         bindings.set(key!(s), Internal::ToggleSummary);
         bindings.set(key!(w), Internal::ToggleWrap);
         bindings.set(key!(b), Internal::ToggleBacktrace);
+        bindings.set(key!(ctrl - g), Internal::CycleGrouping);
+        bindings.set(key!(o), Internal::ToggleGroup);
+        bindings.set(key!(shift - o), Internal::ToggleAllGroups);
         bindings.set(key!(Home), Internal::Scroll(ScrollCommand::Top));
         bindings.set(key!(End), Internal::Scroll(ScrollCommand::Bottom));
         bindings.set(key!(Up), Internal::Scroll(ScrollCommand::Lines(-1)));
@@ -42,6 +45,9 @@ impl Default for KeyBindings {
         bindings.set(key!(ctrl - d), JobRef::Default);
         bindings.set(key!(i), JobRef::Initial);
         bindings.set(key!(p), Internal::TogglePause);
+        bindings.set(key!(shift - i), Internal::ToggleInput);
+        bindings.set(key!(tab), Internal::NextTab);
+        bindings.set(key!(backtab), Internal::PreviousTab);
         // keybindings for some common jobs
         bindings.set(key!(a), JobRef::from_job_name("check-all"));
         bindings.set(key!(c), JobRef::from_job_name("clippy"));
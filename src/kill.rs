@@ -0,0 +1,128 @@
+use {
+    serde::Deserialize,
+    std::fmt,
+};
+
+/// How the command of a job is interrupted, either by a signal sent
+/// to its process group, or by a specific command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "KillConfig")]
+pub enum Kill {
+    /// a signal sent to all the processes of the group of the command
+    /// (SIGKILL is sent after the grace period if some are still alive)
+    Signal(Signal),
+    /// a command, called with the pid of the job's process as last argument
+    Command(Vec<String>),
+}
+
+/// A unix signal which may be sent to stop a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Hup,
+    Int,
+    Quit,
+    Kill,
+    Term,
+    Usr1,
+    Usr2,
+}
+
+/// The `kill` field, as written in the configuration: either a signal
+/// name (eg "SIGINT" or "INT") or the tokens of a command
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum KillConfig {
+    Signal(String),
+    Command(Vec<String>),
+}
+
+#[derive(Debug)]
+pub enum KillError {
+    UnknownSignal(String),
+    EmptyCommand,
+}
+
+impl fmt::Display for KillError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            Self::UnknownSignal(name) => write!(f, "Unknown signal {name:?}"),
+            Self::EmptyCommand => write!(f, "Empty kill command"),
+        }
+    }
+}
+
+impl std::error::Error for KillError {}
+
+impl Default for Kill {
+    /// SIGTERM, which, out of unix, means the command is just killed
+    fn default() -> Self {
+        Self::Signal(Signal::Term)
+    }
+}
+
+impl TryFrom<KillConfig> for Kill {
+    type Error = KillError;
+    fn try_from(config: KillConfig) -> Result<Self, KillError> {
+        match config {
+            KillConfig::Signal(name) => Signal::from_name(&name)
+                .map(Self::Signal)
+                .ok_or(KillError::UnknownSignal(name)),
+            KillConfig::Command(tokens) if tokens.is_empty() => Err(KillError::EmptyCommand),
+            KillConfig::Command(tokens) => Ok(Self::Command(tokens)),
+        }
+    }
+}
+
+impl Signal {
+    /// Parse a signal name, with or without the "SIG" prefix, in any case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        match name {
+            "HUP" => Some(Self::Hup),
+            "INT" => Some(Self::Int),
+            "QUIT" => Some(Self::Quit),
+            "KILL" => Some(Self::Kill),
+            "TERM" => Some(Self::Term),
+            "USR1" => Some(Self::Usr1),
+            "USR2" => Some(Self::Usr2),
+            _ => None,
+        }
+    }
+    #[cfg(unix)]
+    pub fn number(self) -> libc::c_int {
+        match self {
+            Self::Hup => libc::SIGHUP,
+            Self::Int => libc::SIGINT,
+            Self::Quit => libc::SIGQUIT,
+            Self::Kill => libc::SIGKILL,
+            Self::Term => libc::SIGTERM,
+            Self::Usr1 => libc::SIGUSR1,
+            Self::Usr2 => libc::SIGUSR2,
+        }
+    }
+}
+
+#[test]
+fn test_kill_config() {
+    #[derive(Deserialize)]
+    struct Config {
+        kill: Kill,
+    }
+    let kill = |toml: &str| toml::from_str::<Config>(toml).map(|c| c.kill).ok();
+    assert_eq!(kill(r#"kill = "SIGINT""#), Some(Kill::Signal(Signal::Int)));
+    assert_eq!(kill(r#"kill = "term""#), Some(Kill::Signal(Signal::Term)));
+    assert_eq!(
+        kill(r#"kill = ["kill", "-s", "INT"]"#),
+        Some(Kill::Command(vec![
+            "kill".to_string(),
+            "-s".to_string(),
+            "INT".to_string()
+        ])),
+    );
+    assert_eq!(kill(r#"kill = "SIGFOO""#), None);
+    assert_eq!(kill(r#"kill = []"#), None);
+}
@@ -1,37 +1,50 @@
 mod action;
+mod analysis;
 mod app;
 mod args;
 mod auto_refresh;
+mod bench;
+mod cargo_json;
 mod cli;
 mod command_output;
 mod command_result;
 mod config;
+mod config_duration;
 mod defaults;
+mod doctest;
 mod drawing;
 mod examples;
 mod executor;
 mod export;
 mod failure;
+mod grouping;
 mod help_line;
 mod help_page;
 mod ignorer;
 mod internal;
+mod item;
 mod job;
+mod job_input;
 mod job_ref;
 mod job_stack;
 mod keybindings;
+mod kill;
 mod line;
 mod line_analysis;
 mod line_type;
 mod list_jobs;
+mod location;
 mod mission;
 mod mission_location;
 mod on_change_strategy;
+mod pty;
 mod report;
 mod scroll;
 mod settings;
 mod state;
 mod stats;
+mod tab;
+mod task_usage;
 mod tty;
 mod wrap;
 mod wrapped_command_output;
@@ -39,38 +52,51 @@ mod wrapped_report;
 
 pub use {
     action::*,
+    analysis::*,
     args::*,
     auto_refresh::*,
+    bench::*,
+    cargo_json::*,
     cli::*,
     command_output::*,
     command_result::*,
     config::*,
+    config_duration::*,
     defaults::*,
+    doctest::*,
     drawing::*,
     examples::*,
     executor::*,
     export::*,
     failure::*,
+    grouping::*,
     help_line::*,
     help_page::*,
     ignorer::*,
     internal::*,
+    item::*,
     job::*,
+    job_input::*,
     job_ref::*,
     job_stack::*,
     keybindings::*,
+    kill::*,
     line::*,
     line_analysis::*,
     line_type::*,
     list_jobs::*,
+    location::*,
     mission::*,
     mission_location::*,
     on_change_strategy::*,
+    pty::*,
     report::*,
     scroll::*,
     settings::*,
     state::*,
     stats::*,
+    tab::*,
+    task_usage::*,
     tty::*,
     wrap::*,
     wrapped_command_output::*,
@@ -17,6 +17,50 @@ const CSI_ERROR_BODY: &str = CSI_BOLD_WHITE;
 pub struct LineAnalysis {
     pub line_type: LineType,
     pub key: Option<String>,
+    /// the location of the problem, when the line gives it but
+    /// isn't just a location line as understood by bacon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// the message of a title, when it's not the usual second part of the line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl LineAnalysis {
+    /// the analysis of a line which doesn't have a specific meaning
+    pub fn normal() -> Self {
+        Self::of_type(LineType::Normal)
+    }
+    pub fn of_type(line_type: LineType) -> Self {
+        Self {
+            line_type,
+            key: None,
+            location: None,
+            message: None,
+        }
+    }
+    pub fn with_key(
+        line_type: LineType,
+        key: String,
+    ) -> Self {
+        Self {
+            key: Some(key),
+            ..Self::of_type(line_type)
+        }
+    }
+    /// Analyze a line of an output known to be written without style (the
+    /// command was run without `--color always`), whose errors and warnings
+    /// can then only be recognized by their text
+    pub fn from_unstyled(cmd_line: &CommandOutputLine) -> Self {
+        let analysis = Self::from(cmd_line);
+        if analysis.line_type != LineType::Normal {
+            return analysis;
+        }
+        match cmd_line.content.if_unstyled().and_then(as_unstyled_title) {
+            Some(line_type) => Self::of_type(line_type),
+            None => analysis,
+        }
+    }
 }
 
 impl From<&CommandOutputLine> for LineAnalysis {
@@ -44,29 +88,38 @@ impl From<&CommandOutputLine> for LineAnalysis {
             } else if regex_is_match!(r#"^\s+--> [^:\s'"]+:\d+:\d+$"#, content) {
                 // this comes up in test failures to compile
                 LineType::Location
-            } else if regex_is_match!(r#"^thread '.+' panicked at [^:\s'"]+:\d+:\d+:$"#, content) {
-                // this comes up in test failures
+            } else if regex_is_match!(
+                r#"^thread '.+'(?: \(\d+\))? panicked at [^:\s'"]+:\d+:\d+:$"#,
+                content
+            ) {
+                // this comes up in test failures (recent versions of rust
+                // write the id of the thread after its name)
                 LineType::Location
             } else {
                 LineType::Normal
             }
         } else {
-            if let (Some(title), Some(body)) = (content.strings.get(0), content.strings.get(1)) {
+            if let (Some(title), Some(body)) = (content.strings.first(), content.strings.get(1)) {
                 match (
                     title.csi.as_ref(),
                     title.raw.as_ref(),
                     body.csi.as_ref(),
                     body.raw.as_ref(),
                 ) {
-                    (CSI_BOLD_RED, "error", CSI_ERROR_BODY, body_raw)
-                        if body_raw.starts_with(": aborting due to") =>
-                    {
-                        LineType::Title(Kind::Sum)
-                    }
-                    (CSI_BOLD_RED, title_raw, CSI_ERROR_BODY, _)
-                        if title_raw.starts_with("error") =>
-                    {
-                        LineType::Title(Kind::Error)
+                    (
+                        CSI_BOLD_RED | CSI_BOLD_4BIT_BRIGHT_RED,
+                        "error",
+                        CSI_ERROR_BODY,
+                        body_raw,
+                    ) if body_raw.starts_with(": aborting due to") => LineType::Title(Kind::Sum),
+                    (
+                        CSI_BOLD_RED | CSI_BOLD_4BIT_BRIGHT_RED,
+                        title_raw,
+                        CSI_ERROR_BODY,
+                        body_raw,
+                    ) if title_raw.starts_with("error") => determine_error_type(body_raw),
+                    (CSI_BOLD_RED | CSI_BOLD_4BIT_BRIGHT_RED, "error", "", body_raw) => {
+                        determine_cargo_error_type(body_raw)
                     }
                     #[cfg(not(windows))]
                     (CSI_BOLD_YELLOW, "warning", _, body_raw) => {
@@ -76,7 +129,9 @@ impl From<&CommandOutputLine> for LineAnalysis {
                     (CSI_BOLD_YELLOW | CSI_BOLD_4BIT_YELLOW, "warning", _, body_raw) => {
                         determine_warning_type(body_raw, content)
                     }
-                    ("", title_raw, CSI_BOLD_BLUE, "--> ") if is_spaces(title_raw) => {
+                    ("", title_raw, CSI_BOLD_BLUE | CSI_BOLD_4BIT_BRIGHT_BLUE, "--> ")
+                        if is_spaces(title_raw) =>
+                    {
                         LineType::Location
                     }
                     ("", k, CSI_BOLD_RED | CSI_RED, "FAILED") if content.strings.len() == 2 => {
@@ -101,7 +156,61 @@ impl From<&CommandOutputLine> for LineAnalysis {
                 LineType::Normal // empty line
             }
         };
-        LineAnalysis { line_type, key }
+        let line_type = match line_type {
+            LineType::Normal => detail_line_type(&content.to_raw()),
+            line_type => line_type,
+        };
+        LineAnalysis {
+            key,
+            ..LineAnalysis::of_type(line_type)
+        }
+    }
+}
+
+/// Return the type of a title line of rustc or cargo written without
+/// style, for example "error[E0308]: mismatched types" or "warning: unused
+/// import: `std::fs`"
+fn as_unstyled_title(content: &str) -> Option<LineType> {
+    let (_, title, code, body) =
+        regex_captures!(r#"^(error|warning)(\[[^\]\s]+\])?(: .+)$"#, content)?;
+    let line_type = match title {
+        "warning"
+            if is_n_warnings_emitted(body)
+                || regex_is_match!(r#"generated \d+ warnings?"#, body)
+                || regex_is_match!(r#"^: build failed"#, body) =>
+        {
+            LineType::Title(Kind::Sum)
+        }
+        "warning" => LineType::Title(Kind::Warning),
+        _ if regex_is_match!(r#"^: aborting due to"#, body) => LineType::Title(Kind::Sum),
+        _ if !code.is_empty() => LineType::Title(Kind::Error),
+        // without style, the errors of cargo which just sum up others can only be
+        // recognized by their message
+        _ if regex_is_match!(
+            r#"^: (?:(?:doc)?test failed, to rerun|\d+ targets? failed|process didn't exit successfully)"#,
+            body
+        ) =>
+        {
+            LineType::Normal
+        }
+        _ => match determine_cargo_error_type(body) {
+            LineType::Normal => determine_error_type(body),
+            line_type => line_type,
+        },
+    };
+    Some(line_type)
+}
+
+/// Return the type of a line which isn't a title or location of a diagnostic:
+/// a note (eg "  = note: `#[warn(unused_variables)]` on by default"), a help
+/// (eg "help: consider borrowing here"), or a normal line
+pub fn detail_line_type(raw: &str) -> LineType {
+    if regex_is_match!(r#"^\s*(?:= )?note: "#, raw) {
+        LineType::Note
+    } else if regex_is_match!(r#"^\s*(?:= )?help: "#, raw) {
+        LineType::Help
+    } else {
+        LineType::Normal
     }
 }
 
@@ -119,6 +228,34 @@ fn determine_warning_type(
     }
 }
 
+/// Distinguish the errors of the linker from the other compiler errors
+fn determine_error_type(body_raw: &str) -> LineType {
+    if regex_is_match!(r#"^: linking with `.+` failed"#, body_raw) {
+        LineType::Title(Kind::Link)
+    } else {
+        LineType::Title(Kind::Error)
+    }
+}
+
+/// Determine the type of an error line written by cargo itself (its
+/// body isn't styled, contrary to the ones of rustc)
+fn determine_cargo_error_type(body_raw: &str) -> LineType {
+    if regex_is_match!(r#"^: failed to run custom build command"#, body_raw) {
+        LineType::Title(Kind::BuildScript)
+    } else if regex_is_match!(r#"^: could not (?:compile|document)"#, body_raw)
+        || regex_is_match!(
+            r#"^: (?:failed to (?:parse|load) manifest|could not find `Cargo\.toml`|manifest path `.+` does not exist)"#,
+            body_raw
+        )
+    {
+        // "could not compile" (or "document") is usually a sum of the previous errors,
+        // this is checked when building the report
+        LineType::Title(Kind::Cargo)
+    } else {
+        LineType::Normal
+    }
+}
+
 fn is_spaces(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_whitespace())
 }
@@ -132,7 +269,7 @@ fn is_generated_n_warnings(ts: &[TString]) -> bool {
         .any(|ts| regex_is_match!(r#"generated \d+ warnings?"#, &ts.raw))
 }
 fn is_build_failed(ts: Option<&TString>) -> bool {
-    ts.map_or(false, |ts| regex_is_match!(r#"^\s*build failed"#, &ts.raw))
+    ts.is_some_and(|ts| regex_is_match!(r#"^\s*build failed"#, &ts.raw))
 }
 
 /// similar to as_test_result but without the FAILED|ok part
@@ -182,3 +319,25 @@ fn as_test_result(s: &str) -> Option<(&str, bool)> {
 fn as_fail_result_title(s: &str) -> Option<&str> {
     regex_captures!(r#"^---- (.+) stdout ----$"#, s).map(|(_, key)| key)
 }
+
+#[test]
+fn test_cargo_error_types() {
+    for body in [
+        ": failed to parse manifest at `/home/me/foo/Cargo.toml`",
+        ": failed to load manifest for workspace member `/home/me/foo/bar`",
+        ": could not find `Cargo.toml` in `/home/me` or any parent directory",
+        ": manifest path `foo/Cargo.toml` does not exist",
+    ] {
+        assert_eq!(
+            determine_cargo_error_type(body),
+            LineType::Title(Kind::Cargo)
+        );
+    }
+    // errors merely mentioning a manifest aren't cargo's
+    for body in [
+        ": unresolved import `crate::manifest`",
+        ": couldn't read `Cargo.toml`: No such file or directory",
+    ] {
+        assert_eq!(determine_cargo_error_type(body), LineType::Normal);
+    }
+}
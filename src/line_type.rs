@@ -18,11 +18,30 @@ pub enum Kind {
     Error,
     /// a test failure
     TestFail,
+    /// the failure of a build script (eg a panic in build.rs)
+    BuildScript,
+    /// an error of the linker (eg a missing symbol)
+    Link,
+    /// an error told by cargo itself, not by the compiler (eg an
+    /// invalid manifest, or a compilation failing without diagnostic)
+    Cargo,
+    /// the result of a benchmark
+    Bench,
     /// a sum of errors and/or warnings, typically occuring
     /// at the end of the compilation of a package
     Sum,
 }
 
+impl Kind {
+    /// Tell whether items of this kind make the build fail
+    pub fn is_error(self) -> bool {
+        matches!(
+            self,
+            Self::Error | Self::BuildScript | Self::Link | Self::Cargo
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LineType {
     /// the start of a section
@@ -37,11 +56,36 @@ pub enum LineType {
     /// a suggestion to try with backtrace
     BacktraceSuggestion,
 
+    /// a note of a diagnostic, eg "= note: `#[warn(unused)]` on by default"
+    Note,
+
+    /// a help of a diagnostic, eg "help: consider borrowing here"
+    Help,
+
+    /// the header of a group of items, only in displayed reports
+    GroupHeader,
+
     /// any other line
     Normal,
 }
 
 impl LineType {
+    /// Tell whether lines of this type are shown in summary mode
+    pub fn is_in_summary(
+        self,
+        with_helps: bool,
+    ) -> bool {
+        match self {
+            Self::Normal | Self::Note => false,
+            Self::Help => with_helps,
+            _ => true,
+        }
+    }
+    /// Tell whether the line is a detail of its item, written
+    /// without specific structure
+    pub fn is_detail(self) -> bool {
+        matches!(self, Self::Normal | Self::Note | Self::Help)
+    }
     pub fn cols(self) -> usize {
         match self {
             Self::Title(_) => 3,
@@ -64,6 +108,30 @@ impl LineType {
                     item_idx
                 )?;
             }
+            Self::Title(Kind::BuildScript) => {
+                write!(
+                    w,
+                    "\u{1b}[1m\u{1b}[38;5;235m\u{1b}[48;5;202m{:^3}\u{1b}[0m\u{1b}[0m",
+                    item_idx
+                )?;
+            }
+            Self::Title(Kind::Link) => {
+                write!(
+                    w,
+                    "\u{1b}[1m\u{1b}[38;5;235m\u{1b}[48;5;168m{:^3}\u{1b}[0m\u{1b}[0m",
+                    item_idx
+                )?;
+            }
+            Self::Title(Kind::Cargo) => {
+                write!(
+                    w,
+                    "\u{1b}[1m\u{1b}[38;5;255m\u{1b}[48;5;124m{:^3}\u{1b}[0m\u{1b}[0m",
+                    item_idx
+                )?;
+            }
+            Self::Title(Kind::Bench) => {
+                write!(w, "{}", format!("{:^3}", item_idx).black().bold().on_blue())?;
+            }
             Self::Title(Kind::Warning) => {
                 write!(
                     w,
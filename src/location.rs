@@ -0,0 +1,63 @@
+use {
+    lazy_regex::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::fmt,
+};
+
+/// A place in the source code related to an item of the report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    /// the path, as given by the tool, so usually relative
+    pub path: String,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// 1-based column
+    pub column: Option<usize>,
+    /// what the location is about, for example "function defined here"
+    /// or "in this macro invocation"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl Location {
+    /// Parse a location written the way rustc does, for example
+    /// "src/main.rs:3:21" (the line and column being optional)
+    pub fn parse(s: &str) -> Self {
+        let (path, line, column) = match regex_captures!(r"^(.+?):(\d+)(?::(\d+))?:?$", s) {
+            Some((_, path, line, column)) => (path, line.parse().ok(), column.parse().ok()),
+            None => (s, None, None),
+        };
+        Self {
+            path: path.to_string(),
+            line,
+            column,
+            label: None,
+        }
+    }
+    /// Tell whether both locations point to the same place, whatever their labels
+    pub fn same_place(
+        &self,
+        other: &Location,
+    ) -> bool {
+        self.path == other.path && self.line == other.line && self.column == other.column
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -11,9 +11,12 @@ use {
         collections::HashSet,
         path::PathBuf,
         process::Command,
+        time::Duration,
     },
 };
 
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
 static DEFAULT_WATCHES: &[&str] = &["src", "tests", "benches", "examples", "build.rs"];
 
 /// the description of the mission of bacon
@@ -24,6 +27,8 @@ pub struct Mission<'s> {
     pub job_name: String,
     pub cargo_execution_directory: PathBuf,
     pub workspace_root: PathBuf,
+    /// the names and directories of the local packages
+    pub packages: Vec<(String, PathBuf)>,
     pub job: Job,
     files_to_watch: Vec<PathBuf>,
     directories_to_watch: Vec<PathBuf>,
@@ -41,6 +46,7 @@ impl<'s> Mission<'s> {
         let add_all_src = location.intended_is_package;
         let mut files_to_watch: Vec<PathBuf> = Vec::new();
         let mut directories_to_watch = Vec::new();
+        let mut packages = Vec::new();
         if !location.intended_is_package {
             directories_to_watch.push(location.intended_dir.clone());
         }
@@ -50,6 +56,7 @@ impl<'s> Mission<'s> {
                     .manifest_path
                     .parent()
                     .expect("parent of a target folder is a root folder");
+                packages.push((item.name.clone(), item_path.to_path_buf().into()));
                 if add_all_src {
                     let mut watches: Vec<&str> = job.watch.iter().map(|s| s.as_str()).collect();
                     if job.default_watch {
@@ -87,6 +94,7 @@ impl<'s> Mission<'s> {
             job_name,
             cargo_execution_directory,
             workspace_root: location.workspace_root.clone(),
+            packages,
             job,
             files_to_watch,
             directories_to_watch,
@@ -144,13 +152,41 @@ impl<'s> Mission<'s> {
         Ok(())
     }
 
+    /// build (and doesn't call) the external commands of the stages
+    /// of the job (there's only one when the job has no steps)
+    pub fn get_commands(&self) -> Vec<Command> {
+        self.job
+            .stages()
+            .iter()
+            .map(|tokens| self.build_command(tokens))
+            .collect()
+    }
+
+    /// the labels of the stages of the job, made of the first tokens
+    /// of their commands, for example "cargo clippy"
+    pub fn stage_labels(&self) -> Vec<String> {
+        self.job
+            .stages()
+            .iter()
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .take_while(|token| !token.starts_with('-'))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
     /// build (and doesn't call) the external cargo command
-    pub fn get_command(&self) -> Command {
-        let expanded;
-        let command = if self.job.expand_env_vars {
-            expanded = self
-                .job
-                .command
+    fn build_command(
+        &self,
+        tokens: &[String],
+    ) -> Command {
+        let expanded: Vec<String>;
+        let command: &[String] = if self.job.expand_env_vars {
+            expanded = tokens
                 .iter()
                 .map(|token| {
                     regex_replace_all!(r"\$([A-Z0-9a-z_]+)", token, |whole: &str, name| {
@@ -167,7 +203,7 @@ impl<'s> Mission<'s> {
                 .collect();
             &expanded
         } else {
-            &self.job.command
+            tokens
         };
         let mut tokens = command.iter();
         let mut command = Command::new(
@@ -187,7 +223,7 @@ impl<'s> Mission<'s> {
         let mut last_is_features = false;
         let mut tokens = tokens.chain(&self.settings.additional_job_args);
         let mut has_double_dash = false;
-        while let Some(arg) = tokens.next() {
+        for arg in tokens.by_ref() {
             if arg == "--" {
                 // we'll defer addition of the following arguments to after
                 // the addition of the features stuff, so that the features
@@ -261,14 +297,100 @@ impl<'s> Mission<'s> {
         command
     }
 
-    pub fn kill_command(&self) -> Option<Vec<String>> {
-        self.job.kill.clone()
+    /// the user defined line rules applying to this mission, the
+    /// job specific ones first
+    pub fn line_rules(&self) -> Vec<LineRule> {
+        self.job
+            .line_rules
+            .iter()
+            .chain(&self.settings.line_rules)
+            .cloned()
+            .collect()
+    }
+
+    /// how to interrupt the command
+    pub fn kill(&self) -> Kill {
+        self.job.kill.clone().unwrap_or_default()
+    }
+
+    /// how long the command may run before being killed
+    pub fn timeout(&self) -> Option<Duration> {
+        self.job.timeout.map(|timeout| timeout.0)
+    }
+
+    /// how long the processes of the command are given to end after
+    /// the kill signal
+    pub fn grace_period(&self) -> Duration {
+        self.job
+            .grace_period
+            .map_or(DEFAULT_GRACE_PERIOD, |period| period.0)
+    }
+
+    /// whether the command reads its input, so that it must be piped
+    pub fn need_stdin(&self) -> bool {
+        self.job.need_stdin
     }
 
     /// whether we need stdout and not just stderr
     pub fn need_stdout(&self) -> bool {
         self.job.need_stdout
     }
+
+    /// whether stdout and stderr must be written in the same pipe
+    pub fn merge_streams(&self) -> bool {
+        self.job.merge_streams
+    }
+
+    /// whether the command must be run on a pseudo-terminal
+    pub fn use_pty(&self) -> bool {
+        self.job.pty
+    }
+
+    /// whether the output is known to be written without style, no
+    /// command of the job asking for colors (the errors and warnings
+    /// must then be recognized by their text)
+    pub fn is_output_unstyled(&self) -> bool {
+        if self.use_pty() {
+            return false;
+        }
+        if self
+            .job
+            .env
+            .get("CARGO_TERM_COLOR")
+            .is_some_and(|color| color == "always")
+        {
+            return false;
+        }
+        !self.job.stages().iter().any(|tokens| {
+            tokens
+                .iter()
+                .zip(tokens.iter().skip(1))
+                .any(|(a, b)| a == "--color" && b == "always")
+                || tokens.iter().any(|token| token == "--color=always")
+        })
+    }
+}
+
+#[cfg(test)]
+impl<'s> Mission<'s> {
+    /// Build a mission running the job, out of any package
+    pub fn for_job(
+        job_name: &str,
+        job: Job,
+        settings: &'s Settings,
+    ) -> Self {
+        Self {
+            location_name: "test".to_string(),
+            job_name: job_name.to_string(),
+            cargo_execution_directory: PathBuf::new(),
+            workspace_root: PathBuf::new(),
+            packages: Vec::new(),
+            job,
+            files_to_watch: Vec::new(),
+            directories_to_watch: Vec::new(),
+            settings,
+        }
+    }
 }
 
 fn merge_features(
@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io,
+    process::{
+        Command,
+        Stdio,
+    },
+};
+
+/// A pseudo-terminal, on which a job's command can be run so that
+/// it behaves as when launched in a shell (tools checking `isatty`
+/// keep their colors and formats).
+///
+/// The command writes both its stdout and stderr to the slave side,
+/// bacon reads them, merged, from the master side.
+pub struct Pty {
+    pub master: File,
+    slave: File,
+}
+
+#[cfg(target_os = "linux")]
+impl Pty {
+    /// Open a new pseudo-terminal, with the given size in cells
+    pub fn open(
+        width: u16,
+        height: u16,
+    ) -> io::Result<Self> {
+        use std::{
+            ffi::CStr,
+            os::fd::FromRawFd,
+        };
+        let flags = libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC;
+        // SAFETY: the file descriptors are checked before being owned
+        // by the Files, and the name buffer is big enough for ptsname_r
+        unsafe {
+            let master_fd = libc::posix_openpt(flags);
+            if master_fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let master = File::from_raw_fd(master_fd);
+            if libc::grantpt(master_fd) != 0 || libc::unlockpt(master_fd) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut name = [0 as libc::c_char; 256];
+            let errno = libc::ptsname_r(master_fd, name.as_mut_ptr(), name.len());
+            if errno != 0 {
+                return Err(io::Error::from_raw_os_error(errno));
+            }
+            let slave_fd = libc::open(CStr::from_ptr(name.as_ptr()).as_ptr(), flags);
+            if slave_fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let slave = File::from_raw_fd(slave_fd);
+            let size = libc::winsize {
+                ws_row: height,
+                ws_col: width,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            };
+            if libc::ioctl(slave_fd, libc::TIOCSWINSZ, &size) != 0 {
+                warn!(
+                    "failed to set the size of the pty: {}",
+                    io::Error::last_os_error()
+                );
+            }
+            Ok(Self { master, slave })
+        }
+    }
+    /// Make the command's stdin, stdout and stderr the slave side of
+    /// the pseudo-terminal.
+    ///
+    /// The command keeps those handles until they're replaced, so this
+    /// must be undone with `detach_pty` once the child is spawned (otherwise
+    /// the end of the child wouldn't be seen on the master side).
+    pub fn attach(
+        &self,
+        command: &mut Command,
+    ) -> io::Result<()> {
+        command
+            .stdin(Stdio::from(self.slave.try_clone()?))
+            .stdout(Stdio::from(self.slave.try_clone()?))
+            .stderr(Stdio::from(self.slave.try_clone()?));
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Pty {
+    pub fn open(
+        _width: u16,
+        _height: u16,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pseudo-terminals are only supported on Linux",
+        ))
+    }
+    pub fn attach(
+        &self,
+        _command: &mut Command,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Release the handles to the slave side given to the command by `attach`
+pub fn detach_pty(command: &mut Command) {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+}
+
+/// Make the command, when spawned, start a new session whose
+/// controlling terminal is its stdout (ie the pseudo-terminal)
+#[cfg(target_os = "linux")]
+pub fn set_pty_session(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: only async-signal-safe functions are called
+    // between the fork and the exec
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() < 0 || libc::ioctl(1, libc::TIOCSCTTY, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_pty_session(_command: &mut Command) {}
+
+/// Return what a terminal would display of a line read from the
+/// pseudo-terminal: the end of line is "\r\n", and the text before
+/// a carriage return (eg a progress bar) is overwritten
+pub fn pty_line_content(line: &str) -> &str {
+    let line = line.trim_end_matches(['\r', '\n']);
+    line.rsplit('\r').next().unwrap_or(line)
+}
+
+#[test]
+fn test_pty_line_content() {
+    assert_eq!(
+        pty_line_content("   Compiling bacon\r\n"),
+        "   Compiling bacon"
+    );
+    assert_eq!(
+        pty_line_content("    Building [=>   ] 3/10\r\x1b[K   Compiling bacon\r\n"),
+        "\x1b[K   Compiling bacon",
+    );
+    assert_eq!(pty_line_content("\r\n"), "");
+}
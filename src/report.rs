@@ -1,13 +1,17 @@
 use {
     crate::*,
     anyhow::Result,
+    cargo_metadata::Message,
     lazy_regex::*,
     serde::{
         Deserialize,
         Serialize,
     },
     std::{
-        collections::HashSet,
+        collections::{
+            HashMap,
+            HashSet,
+        },
         io,
         path::PathBuf,
     },
@@ -18,9 +22,14 @@ use {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Report {
     pub lines: Vec<Line>,
+    /// the errors, test failures and warnings, in the order of their indexes
+    pub items: Vec<Item>,
     pub stats: Stats,
     pub suggest_backtrace: bool,
     pub output: CommandOutput,
+    /// the resources used by the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TaskUsage>,
 }
 
 impl Report {
@@ -37,7 +46,7 @@ impl Report {
         allow_warnings: bool,
         allow_failures: bool,
     ) -> bool {
-        !(self.stats.errors != 0
+        !(self.stats.all_errors() != 0
             || (!allow_failures && self.stats.test_fails != 0)
             || (!allow_warnings && self.stats.warnings != 0))
     }
@@ -45,34 +54,106 @@ impl Report {
     /// `cargo` command.
     ///
     /// We assume errors and warnings come in the stderr stream while
-    ///  test failures come in stdout
-    pub fn from_lines(cmd_lines: &[CommandOutputLine]) -> Result<Report> {
+    ///  test failures come in stdout.
+    ///
+    /// When the command was called with `--message-format=json`, the
+    /// compiler diagnostics come as JSON records in stdout instead.
+    ///
+    /// The other lines are classified by the analyzer.
+    pub fn from_lines<A: Analyzer + ?Sized>(
+        cmd_lines: &[CommandOutputLine],
+        analyzer: &A,
+    ) -> Result<Report> {
         // we first accumulate warnings, test fails and errors in separate vectors
         let mut warnings = Vec::new();
         let mut errors = Vec::new();
         let mut fails = Vec::new();
         let mut failure_names = HashSet::new();
+        let mut reported_failures = HashSet::new(); // failures already having a section
+        let mut cur_fail_key = None; // the key of the current test failure section
+        let mut cur_doctest = None; // the location of the current failing doctest
+        let mut cur_doctest_len = None; // the number of lines of its code block
         let mut passed_tests = 0;
         let mut cur_err_kind = None; // the current kind among stderr lines
+        let mut has_compile_errors = false; // whether errors were given since the last "could not compile"
         let mut is_in_out_fail = false;
         let mut suggest_backtrace = false;
         for cmd_line in cmd_lines {
-            let line_analysis = LineAnalysis::from(cmd_line);
+            if let Some(message) = as_cargo_message(cmd_line) {
+                // the job was launched with --message-format=json
+                match message {
+                    Message::CompilerMessage(message) => {
+                        if let Some((kind, mut lines)) = diagnostic_lines(&message.message) {
+                            match kind {
+                                Kind::Warning => warnings.append(&mut lines),
+                                _ => {
+                                    has_compile_errors = true;
+                                    errors.append(&mut lines);
+                                }
+                            }
+                        }
+                    }
+                    Message::CompilerArtifact(artifact) => {
+                        debug!("compiler artifact: {:?}", artifact.target.name);
+                    }
+                    Message::BuildFinished(finished) => {
+                        debug!("build finished, success: {}", finished.success);
+                    }
+                    _ => {}
+                }
+                // the following text lines don't belong to the diagnostic
+                cur_err_kind = None;
+                is_in_out_fail = false;
+                continue;
+            }
+            let line_analysis = analyzer.analyze_line(cmd_line);
             let line_type = line_analysis.line_type;
             let mut line = Line {
                 item_idx: 0, // will be filled later
                 line_type,
                 content: cmd_line.content.clone(),
             };
+            if let (LineType::Title(kind), Some(message)) = (line_type, &line_analysis.message) {
+                line.content = TLine::title(kind, message);
+            } else if let (LineType::Title(kind), Some(raw)) =
+                (line_type, line.content.if_unstyled())
+            {
+                if let Some(content) = TLine::styled_title(kind, raw) {
+                    line.content = content;
+                }
+            }
+            // a location found in a line which isn't formatted like a location line
+            let mut location_line = line_analysis.location.map(|location| Line {
+                item_idx: 0,
+                line_type: LineType::Location,
+                content: TLine::location(location),
+            });
+            if line_type == LineType::Location {
+                if let Some(location_line) = location_line.take() {
+                    line = location_line;
+                }
+            }
+            if is_in_out_fail && is_compiler_diagnostic_title(&line) {
+                // the compilation error of a doctest, part of the output of its failure
+                line.line_type = LineType::Normal;
+                fails.push(line);
+                continue;
+            }
             debug!(
                 "{:?}> [{line_type:?}][{:?}]",
                 cmd_line.origin, line_analysis.key
             );
             match (line_type, line_analysis.key) {
                 (LineType::TestResult(r), Some(key)) => {
+                    if cur_err_kind == Some(Kind::TestFail) {
+                        // a test result ends the output of a test failure
+                        cur_err_kind = None;
+                        is_in_out_fail = false;
+                    }
+                    cur_doctest = None;
                     if r {
                         passed_tests += 1;
-                    } else {
+                    } else if !reported_failures.contains(&key) {
                         // we should receive the test failure section later,
                         // right now we just whitelist it
                         failure_names.insert(key);
@@ -83,8 +164,17 @@ impl Report {
                         failure_names.remove(&key);
                         line.content = TLine::failed(&key);
                         fails.push(line);
+                        cur_doctest = doctest_location(&key);
+                        cur_doctest_len = cur_doctest.as_ref().and_then(doctest_block_len);
+                        fails.extend(cur_doctest.as_ref().map(doctest_location_line));
                         is_in_out_fail = true;
                         cur_err_kind = Some(Kind::TestFail);
+                        reported_failures.insert(key.clone());
+                        cur_fail_key = Some(key);
+                    } else if is_in_out_fail && cur_fail_key.as_ref() == Some(&key) {
+                        // another section for the same test (eg stderr after stdout)
+                        line.line_type = LineType::Normal;
+                        fails.push(line);
                     } else {
                         warn!(
                             "unexpected test result failure_names={:?}, key={:?}",
@@ -92,7 +182,7 @@ impl Report {
                         );
                     }
                 }
-                (LineType::Normal, None) => {
+                (LineType::Normal | LineType::Note | LineType::Help, None) => {
                     if line.content.is_blank() && cur_err_kind != Some(Kind::TestFail) {
                         is_in_out_fail = false;
                     }
@@ -101,7 +191,7 @@ impl Report {
                     } else {
                         match cur_err_kind {
                             Some(Kind::Warning) => warnings.push(line),
-                            Some(Kind::Error) => errors.push(line),
+                            Some(kind) if kind.is_error() => errors.push(line),
                             _ => {}
                         }
                     }
@@ -110,12 +200,32 @@ impl Report {
                     // we're not interested in this section
                     cur_err_kind = None;
                     is_in_out_fail = false;
+                    cur_doctest = None;
+                }
+                (LineType::Title(Kind::Cargo), _)
+                    if has_compile_errors && is_could_not_compile(&line) =>
+                {
+                    // a sum of the errors of a package, not an error by itself
+                    has_compile_errors = false;
+                    cur_err_kind = None;
+                    is_in_out_fail = false;
                 }
                 (LineType::Title(kind), _) => {
                     cur_err_kind = Some(kind);
-                    match cur_err_kind {
-                        Some(Kind::Warning) => warnings.push(line),
-                        Some(Kind::Error) => errors.push(line),
+                    match kind {
+                        Kind::Warning => {
+                            warnings.push(line);
+                            warnings.extend(location_line);
+                        }
+                        kind if kind.is_error() => {
+                            if is_could_not_compile(&line) {
+                                has_compile_errors = false;
+                            } else if kind != Kind::Cargo {
+                                has_compile_errors = true;
+                            }
+                            errors.push(line);
+                            errors.extend(location_line);
+                        }
                         _ => {} // before warnings and errors, or in a sum
                     }
                 }
@@ -123,10 +233,32 @@ impl Report {
                     suggest_backtrace = true;
                 }
                 (LineType::Location, _) => {
+                    if let (Some(doctest), Some(original)) = (&cur_doctest, line.location()) {
+                        let mut location = Location::parse(original);
+                        if fix_doctest_location(doctest, cur_doctest_len, &mut location) {
+                            let raw = line.content.to_raw();
+                            line.content = if raw.starts_with("thread '") {
+                                // a panic line, whose message must be kept
+                                let fixed = raw.replace(original, &format!("{location}:"));
+                                TLine::from_raw(fixed)
+                            } else {
+                                TLine::location(location.to_string())
+                            };
+                        }
+                    }
+                    if cur_err_kind == Some(Kind::Error) && is_manifest_location(&line) {
+                        // cargo reports the syntax errors of manifests like rustc
+                        if let Some(title) = errors.last_mut() {
+                            if title.line_type == LineType::Title(Kind::Error) {
+                                title.line_type = LineType::Title(Kind::Cargo);
+                                cur_err_kind = Some(Kind::Cargo);
+                            }
+                        }
+                    }
                     match cur_err_kind {
                         Some(Kind::Warning) => warnings.push(line),
-                        Some(Kind::Error) => errors.push(line),
                         Some(Kind::TestFail) => fails.push(line),
+                        Some(kind) if kind.is_error() => errors.push(line),
                         _ => {} // before warnings and errors, or in a sum
                     }
                 }
@@ -141,6 +273,7 @@ impl Report {
                 line_type: LineType::Title(Kind::TestFail),
                 content: TLine::failed(&key),
             });
+            fails.extend(doctest_location(&key).as_ref().map(doctest_location_line));
             fails.push(Line {
                 item_idx: 0,
                 line_type: LineType::Normal,
@@ -159,6 +292,41 @@ impl Report {
             }
             line.item_idx = item_idx;
         }
+        let mut items = Item::from_lines(&lines);
+        // the same diagnostic may be reported for several targets (eg lib and tests)
+        remove_duplicate_items(&mut lines, &mut items);
+        for line in &mut lines {
+            if !matches!(line.line_type, LineType::Title(_)) {
+                continue;
+            }
+            let Some(item) = items.get(line.item_idx - 1) else {
+                continue;
+            };
+            // codes are shown in titles so that they're visible in summary mode
+            if line.content.code().is_none() {
+                if let Some(code) = &item.code {
+                    line.content.add_code(code);
+                }
+            }
+            // the panic message of a test failure tells more than the test name, and
+            // the same goes for build script panics and missing symbols at link time
+            if matches!(item.kind, Kind::TestFail | Kind::BuildScript | Kind::Link) {
+                if let Some(message) = &item.message {
+                    if line.title_message() != Some(message.as_str()) {
+                        line.content.strings.push(TString {
+                            csi: String::new(),
+                            raw: format!(": {message}"),
+                        });
+                    }
+                }
+            }
+            if item.targets > 1 {
+                line.content.strings.push(TString {
+                    csi: CSI_GREY.to_string(),
+                    raw: format!(" ({} targets)", item.targets),
+                });
+            }
+        }
         // we compute the stats at end because some lines may
         // have been read but not added (at start or end)
         let mut stats = Stats::from(&lines);
@@ -166,94 +334,407 @@ impl Report {
         debug!("stats: {:#?}", &stats);
         Ok(Report {
             lines,
+            items,
             stats,
             suggest_backtrace,
             output: CommandOutput::default(),
+            usage: None,
         })
     }
 
+    /// Build the report of the output of a job whose stages are
+    /// labelled: the lines of each stage are analyzed on their own,
+    /// then the reports are combined, with the items labelled.
+    ///
+    /// When there's only one stage, this is just the report of the analyzer.
+    pub fn of_stages(
+        cmd_lines: &[CommandOutputLine],
+        stage_labels: &[String],
+        analyzer: &dyn Analyzer,
+    ) -> Result<Report> {
+        if stage_labels.len() < 2 {
+            return analyzer.build_report(cmd_lines);
+        }
+        let stage_report = |stage: usize| -> Result<Report> {
+            let stage_lines: Vec<CommandOutputLine> = cmd_lines
+                .iter()
+                .filter(|cmd_line| cmd_line.stage == stage)
+                .cloned()
+                .collect();
+            let mut report = analyzer.build_report(&stage_lines)?;
+            for item in &mut report.items {
+                item.stage = Some(stage_labels[stage].clone());
+            }
+            Ok(report)
+        };
+        let mut report = stage_report(0)?;
+        for stage in 1..stage_labels.len() {
+            report.append(stage_report(stage)?);
+        }
+        Ok(report)
+    }
+    /// Add the items of another report after the ones of this report
+    fn append(
+        &mut self,
+        other: Report,
+    ) {
+        let offset = self.items.len();
+        self.lines.extend(other.lines.into_iter().map(|mut line| {
+            line.item_idx += offset;
+            line
+        }));
+        self.items.extend(other.items.into_iter().map(|mut item| {
+            item.idx += offset;
+            item
+        }));
+        let stats = Stats::from(&self.lines);
+        self.stats = Stats {
+            passed_tests: self.stats.passed_tests + other.stats.passed_tests,
+            skipped_tests: self.stats.skipped_tests + other.stats.skipped_tests,
+            flaky_tests: self.stats.flaky_tests + other.stats.flaky_tests,
+            ignored_tests: self.stats.ignored_tests + other.stats.ignored_tests,
+            measured_tests: self.stats.measured_tests + other.stats.measured_tests,
+            filtered_out_tests: self.stats.filtered_out_tests + other.stats.filtered_out_tests,
+            test_duration: match (self.stats.test_duration, other.stats.test_duration) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            },
+            ..stats
+        };
+        self.suggest_backtrace |= other.suggest_backtrace;
+    }
+    /// Add an item after the other ones, and return it.
+    ///
+    /// The first line must be the title of the item.
+    pub fn push_item(
+        &mut self,
+        mut lines: Vec<Line>,
+    ) -> Option<&mut Item> {
+        let item_idx = self.items.len() + 1;
+        for line in &mut lines {
+            line.item_idx = item_idx;
+            self.stats.add_line(line);
+        }
+        let item = Item::from_lines(&lines).into_iter().next()?;
+        self.lines.append(&mut lines);
+        self.items.push(item);
+        self.items.last_mut()
+    }
+    /// The benchmarks of the report, in order
+    pub fn benches(&self) -> impl Iterator<Item = &Bench> {
+        self.items.iter().filter_map(|item| item.bench.as_ref())
+    }
+    /// Fill the times of the benchmarks of a previous run, and
+    /// show in titles how they changed since
+    pub fn set_previous_benches(
+        &mut self,
+        previous: &[Bench],
+    ) {
+        for item in &mut self.items {
+            let Some(bench) = &mut item.bench else {
+                continue;
+            };
+            bench.previous_time = previous
+                .iter()
+                .find(|previous_bench| previous_bench.name == bench.name)
+                .map(|previous_bench| previous_bench.time);
+            let Some(change) = bench.change_since_previous() else {
+                continue;
+            };
+            let title = self.lines.iter_mut().find(|line| {
+                line.item_idx == item.idx && matches!(line.line_type, LineType::Title(_))
+            });
+            if let Some(title) = title {
+                title.content.strings.push(TString {
+                    csi: CSI_GREY.to_string(),
+                    raw: format!(" [{change:+.2}% since previous run]"),
+                });
+            }
+        }
+    }
+    /// Return the item with the given index
+    pub fn item(
+        &self,
+        item_idx: usize,
+    ) -> Option<&Item> {
+        self.items.iter().find(|item| item.idx == item_idx)
+    }
+
     /// Extract all the diagnostic context, that is all the normal lines
-    /// which have the same item index as the given line.
+    /// of the item with the given index.
     /// Those lines are taken without style and joined with an escaped newline.
     fn extract_raw_diagnostic_context(
         &self,
-        line: &Line,
+        item_idx: usize,
     ) -> String {
         self.lines
             .iter()
-            .filter(|l| l.line_type == LineType::Normal && l.item_idx == line.item_idx)
+            .filter(|l| l.line_type.is_detail() && l.item_idx == item_idx)
             .map(|l| l.content.to_raw())
             .collect::<Vec<String>>()
             .join("\\n")
     }
 
     /// export the report in a file, as the "locations" format
+    ///
+    /// There's a line per location of each item, the primary location first
     pub fn write_locations<W: io::Write>(
         &self,
         w: &mut W,
         mission: &Mission, // used to get the workspace root to normalize locations
         line_format: &str,
     ) -> Result<(), io::Error> {
-        let mut last_kind = "???";
-        let mut message = None;
         let format_has_context = line_format.contains("{context}");
-        for line in &self.lines {
-            match line.line_type {
-                LineType::Title(Kind::Warning) => {
-                    last_kind = "warning";
-                    message = line.title_message();
-                }
-                LineType::Title(Kind::Error) => {
-                    last_kind = "error";
-                    message = line.title_message();
-                }
-                LineType::Title(Kind::TestFail) => {
-                    last_kind = "test";
-                    message = line.title_message();
-                }
-                _ => {}
-            }
-            let Some(location) = line.location() else {
-                continue;
+        for item in &self.items {
+            let kind = match item.kind {
+                Kind::Warning => "warning",
+                Kind::Error => "error",
+                Kind::TestFail => "test",
+                Kind::BuildScript => "build-script",
+                Kind::Link => "link",
+                Kind::Cargo => "cargo",
+                _ => "???",
             };
-            let (_, mut path, file_line, file_column) =
-                regex_captures!(r#"^([^:\s]+):(\d+):(\d+)$"#, location)
-                    .unwrap_or(("", location, "", ""));
-            // we need to make sure the path is absolute
-            let path_buf = PathBuf::from(path);
-            let path_string;
-            if path_buf.is_relative() {
-                path_string = mission
-                    .workspace_root
-                    .join(path)
-                    .to_string_lossy()
-                    .to_string();
-                path = &path_string;
-            }
-            let extracted_context;
             let context = if format_has_context {
-                extracted_context = self.extract_raw_diagnostic_context(line);
-                &extracted_context
+                self.extract_raw_diagnostic_context(item.idx)
             } else {
-                ""
+                String::new()
             };
-            let exported = regex_replace_all!(r#"\{([^\s}]+)\}"#, line_format, |_, key| {
-                match key {
-                    "column" => file_column,
-                    "context" => context,
-                    "kind" => last_kind,
-                    "line" => file_line,
-                    "message" => message.unwrap_or(""),
-                    "path" => path,
-                    _ => {
-                        debug!("unknown export key: {key:?}");
-                        ""
+            let help = item.helps.join(" / ");
+            for location in item.locations() {
+                // we need to make sure the path is absolute
+                let path_buf = PathBuf::from(&location.path);
+                let path = if path_buf.is_relative() {
+                    mission
+                        .workspace_root
+                        .join(path_buf)
+                        .to_string_lossy()
+                        .to_string()
+                } else {
+                    location.path.clone()
+                };
+                let file_line = location.line.map(|l| l.to_string()).unwrap_or_default();
+                let file_column = location.column.map(|c| c.to_string()).unwrap_or_default();
+                let exported = regex_replace_all!(r#"\{([^\s}]+)\}"#, line_format, |_, key| {
+                    match key {
+                        "code" => item.code.as_deref().unwrap_or(""),
+                        "column" => &file_column,
+                        "context" => &context,
+                        "help" => &help,
+                        "kind" => kind,
+                        "label" => location.label.as_deref().unwrap_or(""),
+                        "line" => &file_line,
+                        "message" => item.message.as_deref().unwrap_or(""),
+                        "path" => &path,
+                        _ => {
+                            debug!("unknown export key: {key:?}");
+                            ""
+                        }
                     }
-                }
-            });
-            writeln!(w, "{}", exported)?;
+                });
+                writeln!(w, "{}", exported)?;
+            }
         }
         debug!("exported locations");
         Ok(())
     }
 }
+
+/// Tell whether the line is the title of an error or warning of the compiler,
+/// including its final "aborting due to 2 previous errors"
+fn is_compiler_diagnostic_title(line: &Line) -> bool {
+    match line.line_type {
+        LineType::Title(Kind::Error | Kind::Warning) => true,
+        LineType::Title(Kind::Sum) => line
+            .title_message()
+            .is_some_and(|message| message.starts_with("aborting due to")),
+        _ => false,
+    }
+}
+
+/// Tell whether the line is cargo's "could not compile `x` (lib) due to 2 previous errors"
+/// (or "could not document `x`")
+fn is_could_not_compile(line: &Line) -> bool {
+    line.title_message().is_some_and(|message| {
+        message.starts_with("could not compile") || message.starts_with("could not document")
+    })
+}
+
+/// Tell whether the line locates a problem in a cargo manifest
+fn is_manifest_location(line: &Line) -> bool {
+    line.location()
+        .is_some_and(|location| Location::parse(location).path.ends_with("Cargo.toml"))
+}
+
+/// Build the line locating a doctest, so that it can be jumped to
+fn doctest_location_line(location: &Location) -> Line {
+    Line {
+        item_idx: 0,
+        line_type: LineType::Location,
+        content: TLine::location(location.to_string()),
+    }
+}
+
+/// Remove the items which are the same as a previous one, counting
+/// them in the `targets` of this previous one, then reassign the
+/// indexes of the remaining items and lines
+fn remove_duplicate_items(
+    lines: &mut Vec<Line>,
+    items: &mut Vec<Item>,
+) {
+    let mut unique_items: Vec<Item> = Vec::new();
+    let mut removed_indexes = HashSet::new();
+    for item in items.drain(..) {
+        match unique_items.iter_mut().find(|i| i.is_same_as(&item)) {
+            Some(original) => {
+                original.targets += item.targets;
+                removed_indexes.insert(item.idx);
+            }
+            None => unique_items.push(item),
+        }
+    }
+    if !removed_indexes.is_empty() {
+        debug!("removed {} duplicate items", removed_indexes.len());
+        lines.retain(|line| !removed_indexes.contains(&line.item_idx));
+        let mut new_indexes = HashMap::new();
+        for (i, item) in unique_items.iter_mut().enumerate() {
+            new_indexes.insert(item.idx, i + 1);
+            item.idx = i + 1;
+        }
+        for line in lines.iter_mut() {
+            if let Some(&new_idx) = new_indexes.get(&line.item_idx) {
+                line.item_idx = new_idx;
+            }
+        }
+    }
+    *items = unique_items;
+}
+
+#[test]
+fn test_duplicate_items() {
+    let lines = vec![
+        TLine::title(Kind::Warning, "unused variable: `x`"),
+        TLine::location("src/lib.rs:2:9".to_string()),
+        TLine::title(Kind::Warning, "unused import: `std::fs`"),
+        TLine::location("src/lib.rs:1:5".to_string()),
+        // the same warning, for the test target
+        TLine::title(Kind::Warning, "unused variable: `x`"),
+        TLine::location("src/lib.rs:2:9".to_string()),
+        TLine::title(Kind::Warning, "unused variable: `x`"),
+        TLine::location("src/main.rs:2:9".to_string()),
+    ];
+    let cmd_lines = CommandOutputLine::from_contents(lines, CommandStream::StdErr);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    assert_eq!(report.stats.warnings, 3);
+    let targets: Vec<(usize, usize)> = report
+        .items
+        .iter()
+        .map(|item| (item.idx, item.targets))
+        .collect();
+    assert_eq!(targets, vec![(1, 2), (2, 1), (3, 1)]);
+    assert_eq!(report.lines.len(), 6);
+    assert_eq!(report.lines[5].item_idx, 3);
+    assert_eq!(
+        report.lines[0].content.to_raw(),
+        "warning: unused variable: `x` (2 targets)"
+    );
+}
+
+#[test]
+fn test_cargo_level_failures() {
+    // cargo doesn't style the body of its own errors
+    let cargo_error = |message: &str| TLine {
+        strings: vec![
+            TString {
+                csi: CSI_BOLD_RED.to_string(),
+                raw: "error".to_string(),
+            },
+            TString {
+                csi: String::new(),
+                raw: format!(": {message}"),
+            },
+        ],
+    };
+    let lines = vec![
+        TLine::title_with(
+            CSI_BOLD_RED,
+            "error[E0425]".to_string(),
+            "cannot find value `y` in this scope",
+        ),
+        TLine::location("src/lib.rs:2:5".to_string()),
+        cargo_error("could not compile `foo` (lib) due to 1 previous error"),
+        cargo_error("failed to run custom build command for `bar v0.1.0 (/home/me/bar)`"),
+        TLine::from_raw("  --- stderr".to_string()),
+        TLine::from_raw("  thread 'main' (2231) panicked at build.rs:3:5:".to_string()),
+        TLine::from_raw("  libbar not found".to_string()),
+        TLine::title_with(
+            CSI_BOLD_RED,
+            "error".to_string(),
+            "linking with `cc` failed: exit status: 1",
+        ),
+        TLine::from_raw(
+            "  = note: /usr/bin/ld: main.o: in function `main': undefined reference to `bar_init'"
+                .to_string(),
+        ),
+        cargo_error("could not compile `baz` (bin \"baz\") due to 1 previous error"),
+        cargo_error("could not compile `qux` (lib)"),
+    ];
+    let cmd_lines = CommandOutputLine::from_contents(lines, CommandStream::StdErr);
+    let report = StandardAnalyzer::default()
+        .build_report(&cmd_lines)
+        .unwrap();
+    let items: Vec<(Kind, Option<&str>)> = report
+        .items
+        .iter()
+        .map(|item| (item.kind, item.message.as_deref()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (Kind::Error, Some("cannot find value `y` in this scope")),
+            (Kind::BuildScript, Some("libbar not found")),
+            (Kind::Link, Some("undefined symbol: bar_init")),
+            (Kind::Cargo, Some("could not compile `qux` (lib)")),
+        ]
+    );
+    let build_script_location = report.items[1].location.as_ref().unwrap();
+    assert_eq!(
+        PathBuf::from(&build_script_location.path),
+        PathBuf::from("/home/me/bar").join("build.rs"),
+    );
+    assert_eq!(report.stats.all_errors(), 4);
+}
+
+#[test]
+fn test_report_of_stages() {
+    let output = r#"
+src/a.c:1:1: warning: unused variable 'x'
+src/b.c:2:5: error: 'y' undeclared
+src/c.c:3:1: warning: unused variable 'z'
+"#;
+    let mut cmd_lines = CommandOutputLine::from_tty_text(output.trim(), CommandStream::StdErr);
+    for (cmd_line, stage) in cmd_lines.iter_mut().zip([0, 1, 1]) {
+        cmd_line.stage = stage;
+    }
+    let labels = vec!["make lint".to_string(), "make".to_string()];
+    let report = Report::of_stages(&cmd_lines, &labels, &GnuAnalyzer::default()).unwrap();
+    let items: Vec<(usize, Kind, Option<&str>)> = report
+        .items
+        .iter()
+        .map(|item| (item.idx, item.kind, item.stage.as_deref()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (1, Kind::Warning, Some("make lint")),
+            (2, Kind::Error, Some("make")),
+            (3, Kind::Warning, Some("make")),
+        ]
+    );
+    let line_items: Vec<usize> = report.lines.iter().map(|line| line.item_idx).collect();
+    assert_eq!(line_items, vec![1, 1, 2, 2, 3, 3]);
+    assert_eq!(report.stats.warnings, 2);
+    assert_eq!(report.stats.errors, 1);
+}
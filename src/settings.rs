@@ -18,18 +18,23 @@ pub struct Settings {
     pub additional_job_args: Vec<String>,
     pub additional_alias_args: Option<Vec<String>>,
     pub summary: bool,
+    pub summary_helps: bool,
     pub wrap: bool,
     pub reverse: bool,
+    pub grouping: Grouping,
     pub help_line: bool,
     pub no_default_features: bool,
     pub all_features: bool,
     pub features: Option<String>, // comma separated list
     pub keybindings: KeyBindings,
     pub jobs: HashMap<String, Job>,
+    pub line_rules: Vec<LineRule>,
     pub default_job: ConcreteJobRef,
     pub exports: ExportsSettings,
     pub show_changes_count: bool,
     pub on_change_strategy: Option<OnChangeStrategy>,
+    /// the jobs run concurrently, in tabs, when no job is given in args
+    pub tabs: Vec<ConcreteJobRef>,
 }
 
 impl Default for Settings {
@@ -39,18 +44,22 @@ impl Default for Settings {
             additional_job_args: Default::default(),
             additional_alias_args: Default::default(),
             summary: false,
+            summary_helps: false,
             wrap: true,
             reverse: false,
+            grouping: Grouping::None,
             help_line: true,
             no_default_features: Default::default(),
             all_features: Default::default(),
             features: Default::default(),
             keybindings: Default::default(),
             jobs: Default::default(),
+            line_rules: Default::default(),
             default_job: Default::default(),
             exports: Default::default(),
             show_changes_count: false,
             on_change_strategy: None,
+            tabs: Vec::new(),
         }
     }
 }
@@ -65,12 +74,18 @@ impl Settings {
         if let Some(b) = config.summary {
             self.summary = b;
         }
+        if let Some(b) = config.summary_helps {
+            self.summary_helps = b;
+        }
         if let Some(b) = config.wrap {
             self.wrap = b;
         }
         if let Some(b) = config.reverse {
             self.reverse = b;
         }
+        if let Some(grouping) = config.grouping {
+            self.grouping = grouping;
+        }
         if let Some(b) = config.help_line {
             self.help_line = b;
         }
@@ -88,6 +103,7 @@ impl Settings {
         for (name, job) in &config.jobs {
             self.jobs.insert(name.clone(), job.clone());
         }
+        self.line_rules.extend(config.line_rules.iter().cloned());
         if let Some(default_job) = &config.default_job {
             self.default_job = default_job.clone();
         }
@@ -98,6 +114,9 @@ impl Settings {
         if let Some(b) = config.on_change_strategy {
             self.on_change_strategy = Some(b);
         }
+        if let Some(tabs) = &config.tabs {
+            self.tabs.clone_from(tabs);
+        }
     }
     pub fn apply_args(
         &mut self,
@@ -145,6 +164,9 @@ impl Settings {
         if args.features.is_some() {
             self.features.clone_from(&args.features);
         }
+        if !args.tabs.is_empty() {
+            self.tabs.clone_from(&args.tabs);
+        }
         self.additional_job_args
             .clone_from(&args.additional_job_args);
     }
@@ -157,6 +179,13 @@ impl Settings {
                 bail!("Invalid configuration : default job ({name:?}) not found in jobs");
             }
         }
+        for tab in &self.tabs {
+            if let ConcreteJobRef::Name(name) = tab {
+                if !self.jobs.contains_key(name) {
+                    bail!("Invalid configuration : tab job ({name:?}) not found in jobs");
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -1,13 +1,18 @@
 use {
     crate::*,
     anyhow::Result,
-    std::io::Write,
+    std::{
+        collections::HashSet,
+        io::Write,
+        time::Duration,
+    },
     termimad::{
         Area,
         CompoundStyle,
         MadSkin,
         crossterm::{
             cursor,
+            event::KeyEvent,
             execute,
             style::{
                 Attribute,
@@ -27,12 +32,16 @@ use {
 pub struct AppState<'s> {
     /// the mission to run, with settings
     pub mission: Mission<'s>,
+    /// the analyzer of the output of the mission's command
+    analyzer: Box<dyn Analyzer>,
     /// the lines of a computation in progress
     output: Option<CommandOutput>,
     /// wrapped output for the width of the console
     wrapped_output: Option<WrappedCommandOutput>,
     /// result of a command, hopefully a report
     pub cmd_result: CommandResult,
+    /// the report organized in groups, when grouping is on
+    grouped_report: Option<Report>,
     /// a report wrapped for the size of the console
     wrapped_report: Option<WrappedReport>,
     /// screen width
@@ -47,8 +56,14 @@ pub struct AppState<'s> {
     pub backtrace: bool,
     /// whether we should display only titles and locations
     summary: bool,
+    /// whether the help lines are displayed in summary mode
+    summary_helps: bool,
     /// whether we display the gui bottom-to-top
     reverse: bool,
+    /// how items are grouped
+    grouping: Grouping,
+    /// the names of the groups whose items are hidden
+    collapsed_groups: HashSet<String>,
     /// colors and styles used for status bar
     status_skin: MadSkin,
     /// number of lines hidden on top due to scroll
@@ -67,6 +82,13 @@ pub struct AppState<'s> {
     pub changes_since_last_job_start: usize,
     /// whether to display the count of changes
     pub show_changes_count: bool,
+    /// the line typed for the job's input, when in input mode
+    input: Option<JobInput>,
+    /// the line of the tabs, when several jobs are run concurrently
+    pub tab_bar: Option<TLine>,
+    /// the benchmarks of the last report having some, kept when the
+    /// state is cleared so that the next run can be compared to them
+    previous_benches: Vec<Bench>,
 }
 
 impl<'s> AppState<'s> {
@@ -82,18 +104,27 @@ impl<'s> AppState<'s> {
             .help_line
             .then(|| HelpLine::new(mission.settings));
 
+        let analyzer = mission
+            .job
+            .analyzer
+            .create_analyzer(mission.line_rules(), mission.is_output_unstyled());
         Ok(Self {
+            analyzer,
             output: None,
             wrapped_output: None,
             cmd_result: CommandResult::None,
+            grouped_report: None,
             wrapped_report: None,
             width,
             height,
             computing: true,
             summary: mission.settings.summary,
+            summary_helps: mission.settings.summary_helps,
             wrap: mission.settings.wrap,
             backtrace: false,
             reverse: mission.settings.reverse,
+            grouping: mission.settings.grouping,
+            collapsed_groups: HashSet::new(),
             show_changes_count: mission.settings.show_changes_count,
             status_skin,
             scroll: 0,
@@ -104,6 +135,9 @@ impl<'s> AppState<'s> {
             raw_output: false,
             auto_refresh: AutoRefresh::Enabled,
             changes_since_last_job_start: 0,
+            input: None,
+            tab_bar: None,
+            previous_benches: Vec::new(),
         })
     }
 
@@ -113,7 +147,10 @@ impl<'s> AppState<'s> {
     ) {
         let auto_scroll = self.is_scroll_at_bottom();
         if let Some(output) = self.output.as_mut() {
-            output.push(line);
+            let line_idx = output.push(line);
+            if let Some(wrapped_output) = self.wrapped_output.as_mut() {
+                wrapped_output.forget_from(line_idx);
+            }
             if self.wrap {
                 self.update_wrap(self.width - 1);
             }
@@ -137,6 +174,9 @@ impl<'s> AppState<'s> {
             backtrace: self.backtrace,
         }
     }
+    pub fn analyzer(&self) -> &dyn Analyzer {
+        self.analyzer.as_ref()
+    }
     pub fn take_output(&mut self) -> Option<CommandOutput> {
         self.wrapped_output = None;
         self.output.take()
@@ -161,17 +201,24 @@ impl<'s> AppState<'s> {
             CommandResult::Failure(_) => {
                 debug!("GOT FAILURE");
             }
+            CommandResult::TimedOut(_) => {
+                debug!("GOT TIMEOUT");
+            }
             CommandResult::None => {
                 debug!("GOT NONE ???");
             }
         }
         if let CommandResult::Report(ref mut report) = cmd_result {
+            if report.benches().next().is_some() {
+                report.set_previous_benches(&self.previous_benches);
+                self.previous_benches = report.benches().cloned().collect();
+            }
             // if the last line is empty, we remove it, to
             // avoid a useless empty line at the end
             if report
                 .lines
                 .last()
-                .map_or(false, |line| line.content.is_blank())
+                .is_some_and(|line| line.content.is_blank())
             {
                 report.lines.pop();
             }
@@ -183,6 +230,7 @@ impl<'s> AppState<'s> {
         self.wrapped_output = None;
         self.cmd_result = cmd_result;
         self.computing = false;
+        self.update_grouped_report();
         if reset_scroll {
             self.reset_scroll();
         }
@@ -201,15 +249,18 @@ impl<'s> AppState<'s> {
         debug!("state.clear");
         self.take_output();
         self.cmd_result = CommandResult::None;
+        self.grouped_report = None;
     }
-    /// Start a new task on the current mission
+    /// Start a new task on the current mission, once the previous
+    /// one, which should have been killed, has ended
     pub fn start_computation(
         &mut self,
         executor: &mut MissionExecutor,
+        previous: Option<TaskExecutor>,
     ) -> Result<TaskExecutor> {
         debug!("state.start_computation");
         self.computation_starts();
-        executor.start(self.new_task())
+        executor.start(self.new_task(), previous)
     }
     /// Called when a task has started
     pub fn computation_starts(&mut self) {
@@ -222,6 +273,26 @@ impl<'s> AppState<'s> {
     pub fn computation_stops(&mut self) {
         self.computing = false;
     }
+    /// Stop the computation on an error of its execution (eg a command
+    /// which couldn't be launched), which is shown after the output
+    pub fn computation_fails(
+        &mut self,
+        error: &str,
+    ) {
+        let mut output = self.take_output().unwrap_or_default();
+        let seq = output.lines.last().map_or(0, |line| line.seq + 1);
+        output.push(CommandOutputLine {
+            content: TLine::title(Kind::Error, error),
+            origin: CommandStream::StdErr,
+            seq,
+            ..Default::default()
+        });
+        self.set_result(CommandResult::Failure(Failure {
+            error_code: None,
+            output,
+            usage: None,
+        }));
+    }
     pub fn receive_watch_event(&mut self) {
         self.changes_since_last_job_start += 1;
     }
@@ -232,7 +303,7 @@ impl<'s> AppState<'s> {
     fn scroll_to_bottom(&mut self) {
         let ch = self.content_height();
         let ph = self.page_height();
-        self.scroll = if ch > ph { ch - ph } else { 0 };
+        self.scroll = ch.saturating_sub(ph);
         // we don't set top_item_idx - does it matter?
     }
     fn is_scroll_at_bottom(&self) -> bool {
@@ -251,13 +322,12 @@ impl<'s> AppState<'s> {
     /// get the scroll value needed to go to the last item (if any)
     fn get_last_item_scroll(&self) -> usize {
         if let CommandResult::Report(ref report) = self.cmd_result {
+            let report = self.grouped_report.as_ref().unwrap_or(report);
             if let Some(wrapped_report) = self.wrapped_report.as_ref().filter(|_| self.wrap) {
                 let sub_lines = wrapped_report
                     .sub_lines
                     .iter()
-                    .filter(|line| {
-                        !(self.summary && line.src_line_type(report) == LineType::Normal)
-                    })
+                    .filter(|line| self.is_line_shown(line.src_line_type(report)))
                     .enumerate();
                 for (row_idx, sub_line) in sub_lines {
                     if sub_line.src_line(report).item_idx == self.top_item_idx {
@@ -268,7 +338,7 @@ impl<'s> AppState<'s> {
                 let lines = report
                     .lines
                     .iter()
-                    .filter(|line| !(self.summary && line.line_type == LineType::Normal))
+                    .filter(|line| self.is_line_shown(line.line_type))
                     .enumerate();
                 for (row_idx, line) in lines {
                     if line.item_idx == self.top_item_idx {
@@ -305,13 +375,124 @@ impl<'s> AppState<'s> {
             None => Some(HelpPage::new(self.mission.settings)),
         };
     }
+    /// Tell whether lines of this type are shown, depending on the summary mode
+    fn is_line_shown(
+        &self,
+        line_type: LineType,
+    ) -> bool {
+        !self.summary || line_type.is_in_summary(self.summary_helps)
+    }
     pub fn toggle_summary_mode(&mut self) {
         self.summary ^= true;
         self.try_scroll_to_last_top_item();
     }
+    /// Change the grouping mode, cycling between none, file and package
+    pub fn cycle_grouping(&mut self) {
+        self.grouping = self.grouping.next();
+        self.collapsed_groups.clear();
+        self.regroup();
+    }
+    /// Collapse or expand the group of the item on top of the screen
+    pub fn toggle_group(&mut self) {
+        let Some(name) = self.group_name(self.top_item_idx) else {
+            return;
+        };
+        // we'll keep the header of the group on top
+        let header_item_idx = self.grouped_report.as_ref().and_then(|report| {
+            report
+                .lines
+                .iter()
+                .find(|line| {
+                    line.line_type == LineType::GroupHeader
+                        && self.group_name(line.item_idx).as_ref() == Some(&name)
+                })
+                .map(|line| line.item_idx)
+        });
+        if !self.collapsed_groups.remove(&name) {
+            self.collapsed_groups.insert(name);
+        }
+        if let Some(item_idx) = header_item_idx {
+            self.top_item_idx = item_idx;
+        }
+        self.regroup();
+    }
+    /// Collapse all groups, or expand them all if some are already collapsed
+    pub fn toggle_all_groups(&mut self) {
+        if self.collapsed_groups.is_empty() {
+            if let Some(report) = self.cmd_result.report() {
+                self.collapsed_groups = report
+                    .items
+                    .iter()
+                    .map(|item| self.grouping.group_name(item, &self.mission))
+                    .collect();
+            }
+        } else {
+            self.collapsed_groups.clear();
+        }
+        self.regroup();
+    }
+    /// Whether the items are displayed under group headers, which is
+    /// also the case of the stages of a pipeline
+    pub fn is_grouping(&self) -> bool {
+        self.grouping != Grouping::None || self.mission.job.stages().len() > 1
+    }
+    /// Return the name of the group of the item, if grouping is on
+    fn group_name(
+        &self,
+        item_idx: usize,
+    ) -> Option<String> {
+        if !self.is_grouping() {
+            return None;
+        }
+        self.cmd_result
+            .report()
+            .and_then(|report| report.item(item_idx))
+            .map(|item| self.grouping.group_name(item, &self.mission))
+    }
+    fn update_grouped_report(&mut self) {
+        self.grouped_report =
+            self.cmd_result
+                .report()
+                .filter(|_| self.is_grouping())
+                .map(|report| {
+                    self.grouping
+                        .group(report, &self.mission, &self.collapsed_groups)
+                });
+        self.wrapped_report = None;
+    }
+    /// Rebuild the grouped report after a change of grouping, keeping
+    /// the top item on top if possible
+    fn regroup(&mut self) {
+        self.update_grouped_report();
+        if self.wrap {
+            self.update_wrap(self.width - 1);
+        }
+        self.try_scroll_to_last_top_item();
+    }
     pub fn toggle_backtrace(&mut self) {
         self.backtrace ^= true;
     }
+    /// Enter or leave the mode in which keys are typed in the job's input
+    pub fn toggle_input(&mut self) {
+        self.input = match self.input {
+            Some(_) => None,
+            None => Some(JobInput::default()),
+        };
+    }
+    /// Apply a key to the typed input, when in input mode
+    pub fn apply_input_key(
+        &mut self,
+        key: KeyEvent,
+    ) -> InputAction {
+        let Some(input) = self.input.as_mut() else {
+            return InputAction::Unhandled;
+        };
+        let input_action = input.apply_key(key);
+        if input_action == InputAction::Leave {
+            self.input = None;
+        }
+        input_action
+    }
     pub fn toggle_wrap_mode(&mut self) {
         self.wrap ^= true;
         if self.wrapped_report.is_some() {
@@ -320,7 +501,7 @@ impl<'s> AppState<'s> {
     }
     fn content_height(&self) -> usize {
         if let CommandResult::Report(report) = &self.cmd_result {
-            if self.mission.is_success(report) || self.raw_output {
+            if !self.is_report_shown(report) || self.raw_output {
                 if let Some(wrapped_output) = self.wrapped_output.as_ref() {
                     wrapped_output.sub_lines.len()
                 } else {
@@ -330,7 +511,8 @@ impl<'s> AppState<'s> {
                 if let Some(wrapped_report) = self.wrapped_report.as_ref() {
                     wrapped_report.content_height(self.summary)
                 } else {
-                    report.stats.lines(self.summary)
+                    let report = self.grouped_report.as_ref().unwrap_or(report);
+                    report.stats.lines(self.summary, self.summary_helps)
                 }
             }
         } else if let Some(output) = self.cmd_result.output().or(self.output.as_ref()) {
@@ -343,7 +525,23 @@ impl<'s> AppState<'s> {
         }
     }
     fn page_height(&self) -> usize {
-        self.height.max(3) as usize - 3
+        let bars = 3 + self.tab_bar_height() as usize;
+        (self.height as usize).max(bars) - bars
+    }
+    fn tab_bar_height(&self) -> u16 {
+        if self.tab_bar.is_some() { 1 } else { 0 }
+    }
+    /// draw the line of the tabs
+    fn draw_tab_bar(
+        &self,
+        w: &mut W,
+        y: u16,
+        tab_bar: &TLine,
+    ) -> Result<()> {
+        goto(w, y)?;
+        tab_bar.draw_in(w, self.width as usize)?;
+        clear_line(w)?;
+        Ok(())
     }
     pub fn resize(
         &mut self,
@@ -373,12 +571,39 @@ impl<'s> AppState<'s> {
             self.scroll = cmd.apply(self.scroll, self.content_height(), self.page_height());
         }
     }
+    /// draw the line being typed for the job's input
+    fn draw_input_line(
+        &self,
+        w: &mut W,
+        y: u16,
+        input: &JobInput,
+    ) -> Result<()> {
+        goto(w, y)?;
+        // the end of the line is shown when it's too long
+        let width = self.width as usize;
+        let skip = input
+            .line
+            .chars()
+            .count()
+            .saturating_sub(width.saturating_sub(9));
+        let line: String = input.line.chars().skip(skip).collect();
+        write!(
+            w,
+            "\u{1b}[38;5;235m\u{1b}[48;5;204m input \u{1b}[0m {line}\u{2588}"
+        )?;
+        clear_line(w)?;
+        Ok(())
+    }
     /// draw the grey line containing the keybindings indications
+    /// (or the typed input, in input mode)
     fn draw_help_line(
         &self,
         w: &mut W,
         y: u16,
     ) -> Result<()> {
+        if let Some(input) = &self.input {
+            return self.draw_input_line(w, y, input);
+        }
         if let Some(help_line) = &self.help_line {
             let markdown = help_line.markdown(self);
             if self.height > 1 {
@@ -411,6 +636,30 @@ impl<'s> AppState<'s> {
             if stats.errors > 0 {
                 t_line.add_badge(TString::num_badge(stats.errors, "error", 235, 9));
             }
+            if stats.build_script_fails > 0 {
+                t_line.add_badge(TString::num_badge(
+                    stats.build_script_fails,
+                    "build script failure",
+                    235,
+                    202,
+                ));
+            }
+            if stats.link_errors > 0 {
+                t_line.add_badge(TString::num_badge(
+                    stats.link_errors,
+                    "link error",
+                    235,
+                    168,
+                ));
+            }
+            if stats.cargo_errors > 0 {
+                t_line.add_badge(TString::num_badge(
+                    stats.cargo_errors,
+                    "cargo error",
+                    255,
+                    124,
+                ));
+            }
             if stats.test_fails > 0 {
                 t_line.add_badge(TString::num_badge(stats.test_fails, "fail", 235, 208));
             } else if stats.passed_tests > 0 {
@@ -419,13 +668,47 @@ impl<'s> AppState<'s> {
             if stats.warnings > 0 {
                 t_line.add_badge(TString::num_badge(stats.warnings, "warning", 235, 11));
             }
+            if stats.benches > 0 {
+                let benches = match stats.benches {
+                    1 => "1 bench".to_string(),
+                    n => format!("{n} benches"),
+                };
+                t_line.add_badge(TString::badge(&benches, 235, 12));
+            }
+            if stats.ignored_tests > 0 {
+                let ignored = format!("{} ignored", stats.ignored_tests);
+                t_line.add_badge(TString::badge(&ignored, 235, 250));
+            }
+            if stats.filtered_out_tests > 0 {
+                let filtered_out = format!("{} filtered out", stats.filtered_out_tests);
+                t_line.add_badge(TString::badge(&filtered_out, 235, 250));
+            }
+            if stats.measured_tests > 0 {
+                let measured = format!("{} measured", stats.measured_tests);
+                t_line.add_badge(TString::badge(&measured, 235, 250));
+            }
+            if let Some(test_duration) = stats.test_duration {
+                let test_duration = Duration::from_secs_f64(test_duration);
+                let tests_time = format!("tests {}", format_duration(test_duration));
+                t_line.add_badge(TString::badge(&tests_time, 235, 250));
+            }
         } else if let CommandResult::Failure(failure) = &self.cmd_result {
+            let badge = match failure.error_code {
+                Some(error_code) => format!("Command error code: {error_code}"),
+                None => "Command failed".to_string(),
+            };
+            t_line.add_badge(TString::badge(&badge, 235, 9));
+        } else if let CommandResult::TimedOut(timed_out) = &self.cmd_result {
             t_line.add_badge(TString::badge(
-                &format!("Command error code: {}", failure.error_code),
+                &format!("timed out after {}", format_duration(timed_out.duration)),
                 235,
                 9,
             ));
         }
+        if let Some(usage) = self.cmd_result.usage() {
+            let wall_time = Duration::from_secs_f64(usage.wall_time);
+            t_line.add_badge(TString::badge(&format_duration(wall_time), 235, 153));
+        }
         if self.show_changes_count {
             t_line.add_badge(TString::num_badge(
                 self.changes_since_last_job_start,
@@ -475,11 +758,20 @@ impl<'s> AppState<'s> {
             None
         }
     }
+    /// Tell whether the report has something to show, instead of the
+    /// output of the command
+    fn is_report_shown(
+        &self,
+        report: &Report,
+    ) -> bool {
+        !self.mission.is_success(report) || report.stats.benches > 0
+    }
     fn report_to_draw(&self) -> Option<&Report> {
         self.cmd_result
             .report()
             .filter(|_| !self.raw_output)
-            .filter(|report| !self.mission.is_success(report))
+            .filter(|report| self.is_report_shown(report))
+            .map(|report| self.grouped_report.as_ref().unwrap_or(report))
     }
     fn update_wrap(
         &mut self,
@@ -487,7 +779,7 @@ impl<'s> AppState<'s> {
     ) {
         if let Some(report) = self.report_to_draw() {
             if self.wrapped_report.is_none() {
-                self.wrapped_report = Some(WrappedReport::new(report, width));
+                self.wrapped_report = Some(WrappedReport::new(report, width, self.summary_helps));
                 self.scroll = self.get_last_item_scroll();
             }
         } else if let Some(output) = self.cmd_result.output().or(self.output.as_ref()) {
@@ -534,9 +826,7 @@ impl<'s> AppState<'s> {
                     let mut sub_lines = wrapped_report
                         .sub_lines
                         .iter()
-                        .filter(|sub_line| {
-                            !(self.summary && sub_line.src_line_type(report) == LineType::Normal)
-                        })
+                        .filter(|sub_line| self.is_line_shown(sub_line.src_line_type(report)))
                         .skip(self.scroll);
                     for row_idx in 0..area.height {
                         let y = row_idx + top;
@@ -558,7 +848,7 @@ impl<'s> AppState<'s> {
                     let mut lines = report
                         .lines
                         .iter()
-                        .filter(|line| !(self.summary && line.line_type == LineType::Normal))
+                        .filter(|line| self.is_line_shown(line.line_type))
                         .skip(self.scroll);
                     for row_idx in 0..area.height {
                         let y = row_idx + top;
@@ -623,22 +913,29 @@ impl<'s> AppState<'s> {
         &mut self,
         w: &mut W,
     ) -> Result<()> {
+        let tb = self.tab_bar_height();
         if self.reverse {
             self.draw_help_line(w, 0)?;
             if let Some(help_page) = self.help_page.as_mut() {
                 help_page.draw(w, Area::new(0, 1, self.width, self.height - 1))?;
             } else {
                 self.draw_content(w, 1)?;
-                self.draw_computing(w, self.height - 2)?;
-                self.draw_badges(w, self.height - 1)?;
+                self.draw_computing(w, self.height - 2 - tb)?;
+                self.draw_badges(w, self.height - 1 - tb)?;
+                if let Some(tab_bar) = &self.tab_bar {
+                    self.draw_tab_bar(w, self.height - 1, tab_bar)?;
+                }
             }
         } else {
             if let Some(help_page) = self.help_page.as_mut() {
                 help_page.draw(w, Area::new(0, 0, self.width, self.height - 1))?;
             } else {
-                self.draw_badges(w, 0)?;
-                self.draw_computing(w, 1)?;
-                self.draw_content(w, 2)?;
+                if let Some(tab_bar) = &self.tab_bar {
+                    self.draw_tab_bar(w, 0, tab_bar)?;
+                }
+                self.draw_badges(w, tb)?;
+                self.draw_computing(w, tb + 1)?;
+                self.draw_content(w, tb + 2)?;
             }
             self.draw_help_line(w, self.height - 1)?;
         }
@@ -646,3 +943,39 @@ impl<'s> AppState<'s> {
         Ok(())
     }
 }
+
+#[test]
+fn test_bench_changes_since_previous_run() {
+    let settings = Settings {
+        wrap: false,
+        ..Default::default()
+    };
+    let mut job = Job::from_alias("bench", &settings);
+    job.analyzer = AnalyzerRef::Bench;
+    job.background = false;
+    let mut state = AppState::new(Mission::for_job("bench", job, &settings)).unwrap();
+    let bench_result = |state: &AppState, nanos: &str| {
+        let text = format!("test bench_parse ... bench:       {nanos} ns/iter (+/- 56)");
+        let output = CommandOutput {
+            lines: CommandOutputLine::from_tty_text(&text, CommandStream::StdOut),
+        };
+        let labels = ["cargo bench".to_string()];
+        CommandResult::new(output, None, None, &labels, state.analyzer()).unwrap()
+    };
+    let result = bench_result(&state, "1,000");
+    state.set_result(result);
+    // the job isn't a background one, so the previous report is cleared
+    state.computation_starts();
+    assert!(state.cmd_result.report().is_none());
+    let result = bench_result(&state, "1,100");
+    state.set_result(result);
+    let report = state.cmd_result.report().unwrap();
+    let bench = report.benches().next().unwrap();
+    assert_eq!(bench.previous_time, Some(1000.0));
+    assert!(
+        report.lines[0]
+            .content
+            .to_raw()
+            .ends_with("[+10.00% since previous run]")
+    );
+}
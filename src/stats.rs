@@ -12,36 +12,81 @@ pub struct Stats {
     pub warnings: usize,
     pub errors: usize,
     pub test_fails: usize,
+    /// failures of build scripts
+    pub build_script_fails: usize,
+    pub link_errors: usize,
+    /// errors told by cargo itself, like manifest errors
+    pub cargo_errors: usize,
+    pub benches: usize,
     pub passed_tests: usize,
+    /// tests which weren't run (only known with some test runners)
+    pub skipped_tests: usize,
+    /// tests which passed after having failed (only known with some test runners)
+    pub flaky_tests: usize,
+    /// tests marked as ignored, summed over all test binaries
+    pub ignored_tests: usize,
+    /// benchmarks run by libtest, summed over all test binaries
+    pub measured_tests: usize,
+    /// tests excluded by a filter, summed over all test binaries
+    pub filtered_out_tests: usize,
+    /// the total duration of the tests, in seconds, if known
+    pub test_duration: Option<f64>,
     pub location_lines: usize,
+    pub help_lines: usize,
+    /// other lines, including notes
     pub normal_lines: usize,
+    /// headers of groups, only in reports grouped for display
+    pub group_headers: usize,
 }
 impl From<&Vec<Line>> for Stats {
     fn from(lines: &Vec<Line>) -> Self {
-        lines.iter().fold(Stats::default(), |mut stats, line| {
-            match line.line_type {
-                LineType::Title(Kind::Error) => stats.errors += 1,
-                LineType::Title(Kind::Warning) => stats.warnings += 1,
-                LineType::Title(Kind::TestFail) => stats.test_fails += 1,
-                LineType::Location => stats.location_lines += 1,
-                _ => stats.normal_lines += 1,
-            }
-            stats
-        })
+        let mut stats = Stats::default();
+        for line in lines {
+            stats.add_line(line);
+        }
+        stats
     }
 }
 impl Stats {
+    pub fn add_line(
+        &mut self,
+        line: &Line,
+    ) {
+        match line.line_type {
+            LineType::Title(Kind::Error) => self.errors += 1,
+            LineType::Title(Kind::Warning) => self.warnings += 1,
+            LineType::Title(Kind::TestFail) => self.test_fails += 1,
+            LineType::Title(Kind::BuildScript) => self.build_script_fails += 1,
+            LineType::Title(Kind::Link) => self.link_errors += 1,
+            LineType::Title(Kind::Cargo) => self.cargo_errors += 1,
+            LineType::Title(Kind::Bench) => self.benches += 1,
+            LineType::Location => self.location_lines += 1,
+            LineType::Help => self.help_lines += 1,
+            LineType::GroupHeader => self.group_headers += 1,
+            _ => self.normal_lines += 1,
+        }
+    }
     pub fn lines(
         &self,
         summary: bool,
+        summary_helps: bool,
     ) -> usize {
-        let mut sum = self.warnings + self.errors + self.test_fails + self.location_lines;
+        let mut sum = self.items() + self.location_lines + self.group_headers;
+        if !summary || summary_helps {
+            sum += self.help_lines;
+        }
         if !summary {
             sum += self.normal_lines;
         }
         sum
     }
     pub fn items(&self) -> usize {
-        self.warnings + self.errors + self.test_fails
+        self.warnings + self.all_errors() + self.test_fails + self.benches
+    }
+    /// Return the number of items which make the build fail, whatever
+    /// their kind: compiler errors, build script failures, link errors,
+    /// and cargo errors
+    pub fn all_errors(&self) -> usize {
+        self.errors + self.build_script_fails + self.link_errors + self.cargo_errors
     }
 }
@@ -0,0 +1,202 @@
+use {
+    crate::*,
+    anyhow::Result,
+    crossbeam::channel::{
+        Receiver,
+        bounded,
+    },
+    notify::{
+        RecommendedWatcher,
+        event::{
+            AccessKind,
+            AccessMode,
+            DataChange,
+            EventKind,
+            ModifyKind,
+        },
+    },
+};
+
+/// A job run by the application, with its watcher, its executor and
+/// the state of its display.
+///
+/// There are several tabs when jobs are run concurrently, each one
+/// being computed on changes even when it's not displayed.
+pub struct Tab<'s> {
+    pub state: AppState<'s>,
+    pub executor: MissionExecutor,
+    /// the execution of the current task, if any
+    task_executor: Option<TaskExecutor>,
+    pub on_change_strategy: OnChangeStrategy,
+    /// receiving a message on every relevant change in watched files
+    pub watch_receiver: Receiver<()>,
+    /// the watcher, which must be kept for the files to stay watched
+    _watcher: RecommendedWatcher,
+}
+
+impl<'s> Tab<'s> {
+    /// Watch the files of the mission, and start its first computation
+    pub fn new(mission: Mission<'s>) -> Result<Self> {
+        let mut ignorer = time!(Info, mission.ignorer());
+        let (watch_sender, watch_receiver) = bounded(0);
+        let on_change_strategy = mission
+            .job
+            .on_change_strategy
+            .or(mission.settings.on_change_strategy)
+            .unwrap_or(OnChangeStrategy::WaitThenRestart);
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(we) => {
+                    match we.kind {
+                        EventKind::Modify(ModifyKind::Metadata(_)) => {
+                            info!("ignoring metadata change");
+                            return; // useless event
+                        }
+                        EventKind::Modify(ModifyKind::Data(DataChange::Any)) => {
+                            info!("ignoring 'any' data change");
+                            return; // probably useless event with no real change
+                        }
+                        EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
+                            info!("close write event: {we:?}");
+                        }
+                        EventKind::Access(_) => {
+                            info!("ignoring access event: {we:?}");
+                            return; // probably useless event
+                        }
+                        _ => {
+                            info!("notify event: {we:?}");
+                        }
+                    }
+                    if let Some(ignorer) = ignorer.as_mut() {
+                        match time!(Info, ignorer.excludes_all(&we.paths)) {
+                            Ok(true) => {
+                                debug!("all excluded");
+                                return;
+                            }
+                            Ok(false) => {
+                                debug!("at least one is included");
+                            }
+                            Err(e) => {
+                                warn!("exclusion check failed: {e}");
+                            }
+                        }
+                    }
+                    if let Err(e) = watch_sender.send(()) {
+                        debug!("error when notifying on inotify event: {}", e);
+                    }
+                }
+                Err(e) => warn!("watch error: {:?}", e),
+            })?;
+
+        mission.add_watchs(&mut watcher)?;
+
+        let mut executor = MissionExecutor::new(&mission)?;
+
+        let mut state = AppState::new(mission)?;
+        state.computation_starts();
+        let task_executor = executor.start(state.new_task(), None)?; // first computation
+
+        Ok(Self {
+            state,
+            executor,
+            task_executor: Some(task_executor),
+            on_change_strategy,
+            watch_receiver,
+            _watcher: watcher,
+        })
+    }
+    /// Kill the current task, if any, and start a new one, which
+    /// waits, in its own thread, for the end of the killed one.
+    ///
+    /// A failure to start is displayed in the tab, and doesn't stop
+    /// the application nor the other tabs.
+    pub fn rerun(&mut self) {
+        let previous = self.task_executor.take();
+        if let Some(previous) = &previous {
+            previous.kill();
+        }
+        match self.state.start_computation(&mut self.executor, previous) {
+            Ok(task_executor) => {
+                self.task_executor = Some(task_executor);
+            }
+            Err(e) => {
+                warn!("failed to start the computation: {e:#}");
+                self.state.computation_fails(&format!("{e:#}"));
+            }
+        }
+    }
+    /// Ask for the current task, if any, to be killed, without
+    /// waiting for it to end
+    pub fn kill(&self) {
+        if let Some(task_executor) = &self.task_executor {
+            task_executor.kill();
+        }
+    }
+    /// Kill the current task, if any, and wait for the end of
+    /// its processes
+    pub fn die(&mut self) {
+        if let Some(task_executor) = self.task_executor.take() {
+            task_executor.die();
+        }
+    }
+    /// The execution of the current task, whose input can be written
+    pub fn task_executor(&mut self) -> Option<&mut TaskExecutor> {
+        self.task_executor.as_mut()
+    }
+    /// Build the badges of the tab, for the tab bar: the name of
+    /// its job, then the counts of errors, failures and warnings
+    fn badges(
+        &self,
+        selected: bool,
+    ) -> Vec<TString> {
+        let mut badges = Vec::new();
+        let mut name = self.state.mission.job_name.clone();
+        if self.state.is_computing() {
+            name.push('…');
+        }
+        badges.push(if selected {
+            // black over pink, like the job badge
+            TString::badge(&name, 235, 204)
+        } else {
+            TString::badge(&name, 252, 239)
+        });
+        match &self.state.cmd_result {
+            CommandResult::Report(report) => {
+                let stats = &report.stats;
+                for (count, bg) in [
+                    (stats.all_errors(), 9),
+                    (stats.test_fails, 208),
+                    (stats.warnings, 11),
+                ] {
+                    if count > 0 {
+                        badges.push(TString::badge(&count.to_string(), 235, bg));
+                    }
+                }
+                if stats.all_errors() + stats.test_fails + stats.warnings == 0 {
+                    badges.push(TString::badge("✔", 254, 2));
+                }
+            }
+            CommandResult::Failure(_) | CommandResult::TimedOut(_) => {
+                badges.push(TString::badge("!", 235, 9));
+            }
+            CommandResult::None => {}
+        }
+        badges
+    }
+}
+
+/// Build the line of the tabs, with the selected one highlighted
+pub fn tab_bar(
+    tabs: &[Tab],
+    selected: usize,
+) -> TLine {
+    let mut t_line = TLine::default();
+    for (idx, tab) in tabs.iter().enumerate() {
+        t_line.strings.extend(tab.badges(idx == selected));
+        t_line.strings.push(TString {
+            csi: String::new(),
+            raw: "  ".to_string(),
+        });
+    }
+    t_line
+}
@@ -0,0 +1,66 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The resources used by one execution of a job's command
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TaskUsage {
+    /// the time between the start and the end of the command, in seconds
+    pub wall_time: f64,
+    /// the user and system CPU time of the command and of its
+    /// children, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time: Option<f64>,
+    /// the maximum resident set size of the command or of its
+    /// biggest child, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rss: Option<u64>,
+}
+
+impl TaskUsage {
+    /// Combine the usage of a stage of a pipeline with the usage of
+    /// the following one.
+    ///
+    /// The stages being run in sequence, the wall time of the next
+    /// one, counted from the start of the task, covers both. The CPU
+    /// times known are summed.
+    pub fn then(
+        self,
+        next: Self,
+    ) -> Self {
+        let cpu_time = match (self.cpu_time, next.cpu_time) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        Self {
+            wall_time: next.wall_time,
+            cpu_time,
+            max_rss: self.max_rss.max(next.max_rss),
+        }
+    }
+}
+
+#[test]
+fn test_usage_of_stages() {
+    let first = TaskUsage {
+        wall_time: 1.5,
+        cpu_time: Some(2.0),
+        max_rss: Some(300),
+    };
+    let second = TaskUsage {
+        wall_time: 4.0,
+        cpu_time: None,
+        max_rss: Some(200),
+    };
+    let usage = first.then(second);
+    assert_eq!(usage.wall_time, 4.0);
+    assert_eq!(usage.cpu_time, Some(2.0));
+    assert_eq!(usage.max_rss, Some(300));
+    let usage = usage.then(TaskUsage {
+        wall_time: 5.0,
+        cpu_time: Some(0.5),
+        max_rss: None,
+    });
+    assert_eq!(usage.cpu_time, Some(2.5));
+}
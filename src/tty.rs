@@ -1,6 +1,7 @@
 use {
     crate::*,
     anyhow::*,
+    lazy_regex::regex_captures,
     serde::{
         Deserialize,
         Serialize,
@@ -22,6 +23,9 @@ pub const CSI_RED: &str = "\u{1b}[31m";
 pub const CSI_BOLD_RED: &str = "\u{1b}[1m\u{1b}[38;5;9m";
 pub const CSI_BOLD_ORANGE: &str = "\u{1b}[1m\u{1b}[38;5;208m";
 
+/// Used for the codes of errors and lints
+pub const CSI_GREY: &str = "\u{1b}[38;5;245m";
+
 /// Used for "Blocking"
 pub const CSI_BLUE: &str = "\u{1b}[1m\u{1b}[36m";
 
@@ -35,6 +39,12 @@ pub const CSI_BOLD_BLUE: &str = "\u{1b}[1m\u{1b}[38;5;14m";
 #[cfg(not(windows))]
 pub const CSI_BOLD_BLUE: &str = "\u{1b}[1m\u{1b}[38;5;12m";
 
+/// Used by recent versions of rustc and rustdoc for errors
+pub const CSI_BOLD_4BIT_BRIGHT_RED: &str = "\u{1b}[1m\u{1b}[91m";
+
+/// Used by recent versions of rustc and rustdoc for locations
+pub const CSI_BOLD_4BIT_BRIGHT_BLUE: &str = "\u{1b}[1m\u{1b}[94m";
+
 #[cfg(windows)]
 pub const CSI_BOLD_4BIT_YELLOW: &str = "\u{1b}[1m\u{1b}[33m";
 
@@ -53,7 +63,7 @@ static TAB_REPLACEMENT: &str = "    ";
 /// A more generic parsing would have to:
 /// - parse the csi params (it's simple enough to map but takes code)
 /// - use a simple state machine to keep style (bold, italic, etc.),
-///    foreground color, and background color across tstrings
+///   foreground color, and background color across tstrings
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TString {
     pub csi: String,
@@ -216,6 +226,102 @@ impl TLine {
             ],
         }
     }
+    /// build a title line, styled like the ones of rustc, for example
+    /// "warning: unused variable"
+    pub fn title(
+        kind: Kind,
+        message: &str,
+    ) -> Self {
+        let (csi, title) = match kind {
+            Kind::Warning => (CSI_BOLD_YELLOW, "warning"),
+            Kind::Bench => (CSI_BOLD_BLUE, "bench"),
+            _ => (CSI_BOLD_RED, "error"),
+        };
+        Self::title_with(csi, title.to_string(), message)
+    }
+    /// style a title line received without style (eg "error[E0308]: mismatched
+    /// types" when the command was run without `--color always`), so
+    /// that it's displayed and read like the styled ones
+    pub fn styled_title(
+        kind: Kind,
+        raw: &str,
+    ) -> Option<Self> {
+        let (_, title, message) = regex_captures!(r"^(\w+(?:\[[^\]\s]+\])?): (.+)$", raw)?;
+        let csi = match kind {
+            Kind::Warning => CSI_BOLD_YELLOW,
+            Kind::Bench => CSI_BOLD_BLUE,
+            _ => CSI_BOLD_RED,
+        };
+        Some(Self::title_with(csi, title.to_string(), message))
+    }
+    /// build a title line from its already formatted first part
+    /// (eg "error[E0308]") and its message
+    pub fn title_with(
+        csi: &str,
+        title: String,
+        message: &str,
+    ) -> Self {
+        Self {
+            strings: vec![
+                TString {
+                    csi: csi.to_string(),
+                    raw: title,
+                },
+                TString {
+                    csi: CSI_BOLD.to_string(),
+                    raw: format!(": {}", message),
+                },
+            ],
+        }
+    }
+    /// build a line locating a problem, styled like the ones of rustc,
+    /// for example "  --> src/main.rs:3:21"
+    pub fn location(location: String) -> Self {
+        Self {
+            strings: vec![
+                TString {
+                    csi: "".to_string(),
+                    raw: "  ".to_string(),
+                },
+                TString {
+                    csi: CSI_BOLD_BLUE.to_string(),
+                    raw: "--> ".to_string(),
+                },
+                TString {
+                    csi: "".to_string(),
+                    raw: location,
+                },
+            ],
+        }
+    }
+    /// add the code of an error or lint at the end of a title line,
+    /// for example " [clippy::needless_borrow]"
+    pub fn add_code(
+        &mut self,
+        code: &str,
+    ) {
+        self.strings.push(TString {
+            csi: CSI_GREY.to_string(),
+            raw: format!(" [{code}]"),
+        });
+    }
+    /// if the line is a title with a code, either in rustc's
+    /// format ("error[E0308]") or added by bacon, return this code
+    pub fn code(&self) -> Option<&str> {
+        if let Some((_, code)) = self
+            .strings
+            .first()
+            .and_then(|ts| regex_captures!(r"^\w+\[([^\]\s]+)\]$", &ts.raw))
+        {
+            return Some(code);
+        }
+        self.strings
+            .iter()
+            .skip(2)
+            .filter(|ts| ts.csi == CSI_GREY)
+            .find_map(|ts| regex_captures!(r"^ \[([^\]\s]+)\]$", &ts.raw))
+            .map(|(_, code)| code)
+    }
     pub fn add_badge(
         &mut self,
         badge: TString,
@@ -252,13 +358,13 @@ impl TLine {
         Ok(cols)
     }
     pub fn is_blank(&self) -> bool {
-        return self.strings.iter().all(|s| s.raw.trim().is_empty());
+        self.strings.iter().all(|s| s.raw.trim().is_empty())
     }
     // if this line has no style, return its content
     pub fn if_unstyled(&self) -> Option<&str> {
         if self.strings.len() == 1 {
             self.strings
-                .get(0)
+                .first()
                 .filter(|s| s.csi.is_empty())
                 .map(|s| s.raw.as_str())
         } else {
@@ -302,6 +408,11 @@ impl vte::Perform for TLineBuilder {
         _ignore: bool,
         action: char,
     ) {
+        if action != 'm' {
+            // not a style (eg a cursor move or a line clearing, as
+            // written by progress bars), nothing to keep
+            return;
+        }
         if *params == [0] {
             if let Some(cur) = self.cur.take() {
                 self.strings.push(cur);
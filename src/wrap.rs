@@ -48,9 +48,9 @@ pub struct SubLine {
 
 impl SubLine {
     pub fn is_continuation(&self) -> bool {
-        self.sub_strings.get(0).map_or(false, |sub_string| {
-            sub_string.string_idx != 0 || sub_string.byte_start != 0
-        })
+        self.sub_strings
+            .first()
+            .is_some_and(|sub_string| sub_string.string_idx != 0 || sub_string.byte_start != 0)
     }
     pub fn src_line<'r>(
         &self,
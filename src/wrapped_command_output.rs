@@ -27,6 +27,20 @@ impl WrappedCommandOutput {
         }
     }
 
+    /// Forget the wrapping of the lines starting at `line_idx`, so
+    /// that they're wrapped again on next update (eg because a line
+    /// was inserted there)
+    pub fn forget_from(
+        &mut self,
+        line_idx: usize,
+    ) {
+        if line_idx < self.wrapped_lines_count {
+            self.sub_lines
+                .retain(|sub_line| sub_line.line_idx < line_idx);
+            self.wrapped_lines_count = line_idx;
+        }
+    }
+
     /// Assuming the width is the same and the lines already handled
     /// didn't change, wrap and add the lines which weren't.
     pub fn update(
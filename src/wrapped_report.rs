@@ -12,9 +12,11 @@ impl WrappedReport {
     /// compute a new wrapped report for a width and report.
     ///
     /// width is the total area width, including the scrollbar.
+    /// `summary_helps` tells whether the help lines are shown in summary mode.
     pub fn new(
         report: &Report,
         width: u16,
+        summary_helps: bool,
     ) -> Self {
         debug!("wrapping report");
         let sub_lines = wrap(&report.lines, width);
@@ -24,7 +26,7 @@ impl WrappedReport {
                 report
                     .lines
                     .get(sl.line_idx)
-                    .map_or(true, |l| l.line_type != LineType::Normal)
+                    .map_or(true, |l| l.line_type.is_in_summary(summary_helps))
             })
             .count();
         Self {
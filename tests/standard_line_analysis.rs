@@ -16,9 +16,15 @@ fn test_standard_line_analysis() {
         let file = std::fs::File::open(&path).unwrap();
         let reader = std::io::BufReader::new(file);
         let export: AnalysisExport = serde_json::from_reader(reader).unwrap();
+        // the files of outputs written without style are named so
+        let unstyled = name.to_string_lossy().starts_with("uncolored");
         for line_entry in export.lines {
             // checking that we reproduce the same analysis
-            let analysis = LineAnalysis::from(&line_entry.line);
+            let analysis = if unstyled {
+                LineAnalysis::from_unstyled(&line_entry.line)
+            } else {
+                LineAnalysis::from(&line_entry.line)
+            };
             if analysis != line_entry.analysis {
                 println!("Wrong analysis in {:?} for {:#?}", name, line_entry.line);
                 println!("Expected: {:?}", line_entry.analysis);